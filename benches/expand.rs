@@ -0,0 +1,33 @@
+//! Substitution of a file which references the same variable of the running process many times.
+//! Run with `cargo bench`.
+
+use std::time::Instant;
+
+use zenv::Lines;
+
+const VARS: usize = 1_000;
+const RUNS: usize = 100;
+
+fn main() {
+    std::env::set_var("ZENV_BENCH_HOME", "/home/zenv");
+
+    let src: String = (0..VARS)
+        .map(|i| format!("DIR_{}=\"${{ZENV_BENCH_HOME}}/dir/{}\"\n", i, i))
+        .collect();
+
+    let lines = Lines::from(src.as_str());
+
+    let start = Instant::now();
+
+    for _ in 0..RUNS {
+        assert_eq!(lines.expand().len(), VARS);
+    }
+
+    let elapsed = start.elapsed();
+
+    println!(
+        "expand: {} vars referencing one env var, {:?} per run",
+        VARS,
+        elapsed / RUNS as u32
+    );
+}
@@ -0,0 +1,78 @@
+//! Peak memory of parsing a large file, read into a `String` vs memory mapped. Each approach runs
+//! in its own process, as the peak can't be reset. Run with `cargo bench --features mmap`, only
+//! on Linux as the peak is read from `/proc`.
+
+use std::{env, fs, process::Command};
+
+use zenv::{Lines, Mmap, ParseOptions};
+
+const VARS: usize = 200_000;
+
+// Peak resident memory of the current process, and the current anonymous (heap) and file backed
+// (the mapped file, which can be reclaimed by the kernel) parts of it
+fn memory() -> String {
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+
+    ["VmHWM:", "RssAnon:", "RssFile:"]
+        .iter()
+        .map(|field| {
+            let val = status
+                .lines()
+                .find_map(|x| x.strip_prefix(field))
+                .map_or("unknown", str::trim);
+
+            format!("{} {}", field, val)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn parse(path: &str, mode: &str) {
+    let opts = ParseOptions::default();
+
+    // Measured while the source and the lines are still alive
+    let (len, memory) = match mode {
+        "read" => {
+            let src = fs::read_to_string(path).unwrap();
+            let lines = Lines::parse(&src, &opts).unwrap();
+            (lines.len(), memory())
+        }
+        _ => {
+            // SAFETY: The file is not modified while it is mapped
+            let map = unsafe { Mmap::open(path) }.unwrap();
+            let lines = Lines::parse(map.as_str().unwrap(), &opts).unwrap();
+            (lines.len(), memory())
+        }
+    };
+
+    assert_eq!(len, VARS);
+    println!("{}: {}", mode, memory);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if let [_, path, mode] = args.as_slice() {
+        return parse(path, mode);
+    }
+
+    let path = env::temp_dir().join(format!("zenv-bench-{}.env", std::process::id()));
+    let src: String = (0..VARS)
+        .map(|i| format!("GENERATED_KEY_{}=\"generated value of the key {}\"\n", i, i))
+        .collect();
+
+    fs::write(&path, &src).unwrap();
+    println!("file: {} bytes", src.len());
+
+    for mode in ["read", "mmap"] {
+        let status = Command::new(env::current_exe().unwrap())
+            .arg(&path)
+            .arg(mode)
+            .status()
+            .unwrap();
+
+        assert!(status.success());
+    }
+
+    fs::remove_file(&path).ok();
+}
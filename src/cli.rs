@@ -1,8 +1,153 @@
-use std::ffi::OsString;
+use std::{env, ffi::OsString, path::Path, str::FromStr, time::Duration};
 
 use pico_args::Arguments;
+use zenv::{Compat, Encoding, KeyTransform, ParseOptions};
 
-use crate::info::{DESC, NAME, VERSION};
+use crate::{
+    color::{self, Color},
+    crypto,
+    format::Format,
+    info::{DESC, NAME, VERSION},
+    rlimit::Limits,
+};
+
+// Subcommands, which are only recognized as the first argument
+const SUBCOMMANDS: [&str; 7] = [
+    "set",
+    "get",
+    "comment-out",
+    "uncomment",
+    "lint",
+    "compare",
+    "encrypt",
+];
+
+// Options which take a value, needed to find the command when `--` is omitted
+const VALUE_OPTIONS: [&str; 33] = [
+    "--color",
+    "-f",
+    "--file",
+    "--expect-sha256",
+    "--decrypt-key",
+    "--hash-comments-require-space",
+    "--key-transform",
+    "--encoding",
+    "--compat",
+    "--expand-sigil",
+    "--url",
+    "--toml",
+    "--only",
+    "--interpolate-from",
+    "--max-var-size",
+    "--max-env-size",
+    "--unset",
+    "--add-prefix",
+    "--format",
+    "--print-shell",
+    "--set",
+    "--append-path",
+    "--prepend-path",
+    "--require",
+    "--inherit",
+    "--pid-file",
+    "--on-spawn-error",
+    "--retry",
+    "--retry-delay",
+    "--limit-nofile",
+    "--limit-memory",
+    "--template",
+    "--out",
+];
+
+// What to do if the command can't be found
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OnSpawnError {
+    // Exit with 127
+    #[default]
+    Fail,
+
+    // Warn and carry on, exiting with 0 if nothing else fails
+    Ignore,
+}
+
+impl FromStr for OnSpawnError {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fail" => Ok(Self::Fail),
+            "ignore" => Ok(Self::Ignore),
+            x => Err(format!(
+                "Invalid value `{}` for --on-spawn-error, expected one of fail or ignore",
+                x
+            )),
+        }
+    }
+}
+
+// Parses `KEY=value`
+fn key_value(x: &str) -> Result<(String, String), String> {
+    match x.split_once('=') {
+        Some((k, v)) => Ok((k.to_string(), v.to_string())),
+        None => Err(format!("Expected `KEY=value` - `{}`", x)),
+    }
+}
+
+// Parses `500ms`, `2s` or `1m`, where a plain number is in seconds
+fn duration(x: &str) -> Result<Duration, String> {
+    let idx = x.find(|c: char| !c.is_ascii_digit()).unwrap_or(x.len());
+    let (n, unit) = x.split_at(idx);
+
+    let n: u64 = n
+        .parse()
+        .map_err(|_| format!("Invalid duration `{}`, expected i.e. 500ms, 2s or 1m", x))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(n)),
+        "" | "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n.saturating_mul(60))),
+        _ => Err(format!(
+            "Invalid duration `{}`, expected i.e. 500ms, 2s or 1m",
+            x
+        )),
+    }
+}
+
+// Expands a leading `~/` to the home directory, as only the interactive shells do it
+fn expand_home(path: &str) -> String {
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .filter(|x| !x.is_empty());
+
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest).to_string_lossy().into_owned(),
+        _ => path.to_string(),
+    }
+}
+
+pub enum Subcommand {
+    // Update or add the `KEY=value` pair in the file
+    Set(String),
+
+    // Print the value of the key
+    Get(String),
+
+    // Comment out the definitions of the key
+    CommentOut(String),
+
+    // Uncomment the definitions of the key
+    Uncomment(String),
+
+    // Report the errors and the warnings of the files
+    Lint,
+
+    // Compare the variables of the files with the current environment
+    Compare,
+
+    // Print the file encrypted with the key
+    Encrypt,
+}
 
 pub struct Cli {
     // Print help information
@@ -10,11 +155,182 @@ pub struct Cli {
 
     pub version: bool,
 
+    // Run every argument as a separate command
+    pub parallel: bool,
+
+    // Print a summary before running the command
+    pub verbose: bool,
+
+    // Whether to substitute the variables in the command and its arguments
+    pub expand_args: bool,
+
     // Whether to substitute variables or not
     pub expand: bool,
 
-    // Path to .env file
-    path: Option<String>,
+    // Write the variables to a temporary file, for the commands which read one
+    pub emit_env_file: bool,
+
+    // Whether to parse heredoc style values
+    heredoc: bool,
+
+    // Whether to drop the invalid lines instead of failing
+    lenient: bool,
+
+    // Whether to use the `# zenv:if` blocks
+    conditionals: bool,
+
+    // Whether a trailing `\` continues the value on the next line
+    line_continuation: bool,
+
+    // Whether the keys can have `.`, `-` and `/`
+    relaxed_keys: bool,
+
+    // Whether to keep the quotes around the values
+    keep_quotes: bool,
+
+    // Whether the keys can be quoted
+    allow_quoted_keys: bool,
+
+    // Whether the keys can be indexed i.e. `KEY[0]`
+    allow_brackets: bool,
+
+    // Whether `#` needs a whitespace before it to start a comment
+    hash_comments_require_space: bool,
+
+    // Whether to fallback to the process env while substituting
+    pub no_env_fallback: bool,
+
+    // Print the variables in the dotenv format
+    print: bool,
+
+    // Print the variables as shell exports
+    export: bool,
+
+    // Print the variables as json
+    json: bool,
+
+    // Print the variables as the `docker run` flags
+    docker_args: bool,
+
+    // Print the variables with the export syntax of the shell
+    print_shell: Option<Format>,
+
+    // Format to print the variables
+    format: Option<Format>,
+
+    // Sort the printed variables by the key
+    pub sort: bool,
+
+    // Print the leading comment block of the first file before the variables
+    pub print_header: bool,
+
+    // Print the whole environment of the command instead of only the file
+    pub print_effective: bool,
+
+    // Print the undefined variables which are referenced by the substitution
+    pub print_missing: bool,
+
+    // Encode the new lines as `\n` while printing
+    pub escape_newlines: bool,
+
+    // When to color the help and the errors
+    pub color: Color,
+
+    // Rules of the substitution
+    pub compat: Compat,
+
+    // Character which starts a variable in the substitution
+    pub sigil: char,
+
+    // How to decode the files
+    pub encoding: Encoding,
+
+    // How to normalize the keys
+    pub key_transform: KeyTransform,
+
+    // Files to download, which are loaded after the local files
+    pub urls: Vec<String>,
+
+    // TOML files whose [env] table is loaded after the .env files
+    pub tomls: Vec<String>,
+
+    // Variables which take precedence over the files
+    pub vars: Vec<(String, String)>,
+
+    // Directories to add at the end of the PATH-like variables
+    pub append_path: Vec<(String, String)>,
+
+    // Directories to add at the start of the PATH-like variables
+    pub prepend_path: Vec<(String, String)>,
+
+    // Fail if the files don't have any variable
+    pub require_nonempty: bool,
+
+    // Fail if `compare` finds any variable which is not the same
+    pub strict: bool,
+
+    // Fail if a variable overrides an inherited one
+    pub fail_on_override: bool,
+
+    // Warn if a variable overrides an inherited one
+    pub warn_on_override: bool,
+
+    // Files which are only used by the substitution
+    pub interpolate_from: Vec<String>,
+
+    // Patterns of the variables to pass or print, the rest are dropped
+    pub only: Vec<String>,
+
+    // Patterns of the variables to remove, also from the inherited ones
+    pub unset: Vec<String>,
+
+    // Prefix added to every key of the files, after --only and --unset
+    pub add_prefix: Option<String>,
+
+    // Limit of a single value in bytes
+    pub max_var_size: Option<usize>,
+
+    // Limit of all the variables in bytes, as `KEY=value`
+    pub max_env_size: Option<usize>,
+
+    // Variables which must be present and not empty
+    pub require: Vec<String>,
+
+    // Variables to copy from the parent process into an otherwise empty environment
+    pub inherit: Vec<String>,
+
+    // Text file to substitute the variables in, instead of running a command
+    pub template: Option<String>,
+
+    // File to write the substituted template, instead of stdout
+    pub out: Option<String>,
+
+    // File to write the PID of the spawned command
+    pub pid_file: Option<String>,
+
+    // What to do if the command can't be found
+    pub on_spawn_error: OnSpawnError,
+
+    // Resource limits of the command, only on Unix
+    pub limits: Limits,
+
+    // How many times to run the command again if it fails
+    pub retry: usize,
+
+    // Time to wait before running the command again
+    pub retry_delay: Duration,
+
+    // Path to .env file(s)
+    pub paths: Vec<String>,
+
+    // Expected digest of each file, in the same order
+    pub sha256: Vec<String>,
+
+    // Key of the encrypted files, otherwise taken from `ZENV_KEY`
+    decrypt_key: Option<String>,
+
+    // Subcommand to run instead of a command
+    pub subcommand: Option<Subcommand>,
 
     // Name of the command
     binary: Option<OsString>,
@@ -29,31 +345,201 @@ impl Cli {
         let mut args: Vec<_> = std::env::args_os().collect();
         args.remove(0); // remove the executable path.
 
-        // Find and process `--`.
-        let bin_args = if let Some(dash_dash) = args.iter().position(|arg| arg == "--") {
-            // Store all arguments following ...
-            let later_args = args.drain(dash_dash + 1..).collect();
-            // .. then remove the `--`
-            args.pop();
-            later_args
-        } else {
-            Vec::new()
+        // Find and process the command, which starts after `--` or at the first non-option
+        let bin_args = match Self::command_start(&args) {
+            Some(start) if args[start] == "--" => {
+                // Store all arguments following ...
+                let later_args = args.drain(start + 1..).collect();
+                // .. then remove the `--`
+                args.pop();
+                later_args
+            }
+            Some(start) => args.drain(start..).collect(),
+            None => Vec::new(),
         };
 
         // Now pass the remaining arguments through to `pico_args`.
         let mut args = Arguments::from_vec(args);
         let mut bin_args = bin_args.into_iter();
-        let res = Cli {
+        let subcommand = args.subcommand().map_err(|e| e.to_string())?;
+        let mut res = Cli {
             help: args.contains(["-h", "--help"]),
             version: args.contains(["-v", "--version"]),
+            parallel: args.contains("--parallel"),
+            verbose: args.contains("--verbose"),
             expand: args.contains(["-x", "--expand"]),
-            path: args
-                .opt_value_from_str(["-f", "--file"])
+            expand_args: args.contains("--expand-args"),
+            emit_env_file: args.contains("--emit-env-file"),
+            heredoc: args.contains("--heredoc"),
+            lenient: args.contains("--lenient"),
+            allow_brackets: args.contains("--allow-brackets"),
+            relaxed_keys: args.contains("--relaxed-keys"),
+            conditionals: args.contains("--conditionals"),
+            line_continuation: args.contains("--line-continuation"),
+            keep_quotes: args.contains("--keep-quotes"),
+            allow_quoted_keys: args.contains("--allow-quoted-keys"),
+            hash_comments_require_space: args
+                .opt_value_from_str("--hash-comments-require-space")
+                .map_err(|e| e.to_string())?
+                .unwrap_or(true),
+            no_env_fallback: args.contains("--no-env-fallback"),
+            print: args.contains("--print"),
+            export: args.contains("--export"),
+            json: args.contains("--json"),
+            docker_args: args.contains("--docker-args"),
+            print_shell: args
+                .opt_value_from_fn("--print-shell", Format::shell)
+                .map_err(|e| e.to_string())?,
+            format: args
+                .opt_value_from_str("--format")
+                .map_err(|e| e.to_string())?,
+            sort: args.contains("--sort"),
+            print_header: args.contains("--print-header"),
+            print_effective: args.contains("--print-effective"),
+            print_missing: args.contains("--print-missing"),
+            escape_newlines: args.contains("--escape-newlines"),
+            color: args
+                .opt_value_from_str("--color")
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            compat: args
+                .opt_value_from_str("--compat")
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            sigil: args
+                .opt_value_from_fn("--expand-sigil", |x| {
+                    let mut chars = x.chars();
+
+                    match (chars.next(), chars.next()) {
+                        // Letters, digits and `_` are part of the name
+                        (Some(c), None) if c.is_ascii_punctuation() && !"_{}\\".contains(c) => {
+                            Ok(c)
+                        }
+                        _ => Err(format!(
+                            "Invalid sigil `{}`, expected a symbol i.e. % or @",
+                            x
+                        )),
+                    }
+                })
+                .map_err(|e| e.to_string())?
+                .unwrap_or('$'),
+            encoding: args
+                .opt_value_from_str("--encoding")
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            key_transform: args
+                .opt_value_from_str("--key-transform")
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            require_nonempty: args.contains("--require-nonempty"),
+            strict: args.contains("--strict"),
+            fail_on_override: args.contains("--fail-on-override"),
+            warn_on_override: args.contains("--warn-on-override"),
+            urls: args.values_from_str("--url").map_err(|e| e.to_string())?,
+            tomls: args.values_from_str("--toml").map_err(|e| e.to_string())?,
+            interpolate_from: args
+                .values_from_str("--interpolate-from")
+                .map_err(|e| e.to_string())?,
+            max_var_size: args
+                .opt_value_from_str("--max-var-size")
+                .map_err(|e| e.to_string())?,
+            max_env_size: args
+                .opt_value_from_str("--max-env-size")
+                .map_err(|e| e.to_string())?,
+            only: args.values_from_str("--only").map_err(|e| e.to_string())?,
+            unset: args.values_from_str("--unset").map_err(|e| e.to_string())?,
+            add_prefix: args
+                .opt_value_from_str("--add-prefix")
+                .map_err(|e| e.to_string())?,
+            vars: args
+                .values_from_fn("--set", key_value)
+                .map_err(|e| e.to_string())?,
+            append_path: args
+                .values_from_fn("--append-path", key_value)
                 .map_err(|e| e.to_string())?,
+            prepend_path: args
+                .values_from_fn("--prepend-path", key_value)
+                .map_err(|e| e.to_string())?,
+            require: args
+                .values_from_str("--require")
+                .map_err(|e| e.to_string())?,
+            inherit: args
+                .values_from_str("--inherit")
+                .map_err(|e| e.to_string())?,
+            template: args
+                .opt_value_from_str("--template")
+                .map_err(|e| e.to_string())?,
+            out: args
+                .opt_value_from_str("--out")
+                .map_err(|e| e.to_string())?,
+            pid_file: args
+                .opt_value_from_str("--pid-file")
+                .map_err(|e| e.to_string())?,
+            on_spawn_error: args
+                .opt_value_from_str("--on-spawn-error")
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            retry: args
+                .opt_value_from_str("--retry")
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            retry_delay: args
+                .opt_value_from_fn("--retry-delay", duration)
+                .map_err(|e| e.to_string())?
+                .unwrap_or(Duration::from_secs(1)),
+            limits: Limits {
+                nofile: args
+                    .opt_value_from_str("--limit-nofile")
+                    .map_err(|e| e.to_string())?,
+                memory: args
+                    .opt_value_from_str("--limit-memory")
+                    .map_err(|e| e.to_string())?,
+            },
+            paths: args
+                .values_from_fn(["-f", "--file"], |x| Ok::<_, String>(expand_home(x)))
+                .map_err(|e| e.to_string())?,
+            sha256: args
+                .values_from_fn("--expect-sha256", |x| {
+                    if x.len() == 64 && x.chars().all(|c| c.is_ascii_hexdigit()) {
+                        Ok(x.to_ascii_lowercase())
+                    } else {
+                        Err(format!("Invalid SHA-256 `{}`, expected 64 hex digits", x))
+                    }
+                })
+                .map_err(|e| e.to_string())?,
+            decrypt_key: args
+                .opt_value_from_str("--decrypt-key")
+                .map_err(|e| e.to_string())?,
+            subcommand: None,
             binary: bin_args.next(),
             bin_args: bin_args.collect(),
         };
 
+        // Free arguments can only be parsed after all the options
+        res.subcommand = match subcommand.as_deref() {
+            Some("set") => Some(Subcommand::Set(
+                args.free_from_str()
+                    .map_err(|_| "`set` requires a `KEY=value` argument")?,
+            )),
+            Some("get") => Some(Subcommand::Get(
+                args.free_from_str()
+                    .map_err(|_| "`get` requires a `KEY` argument")?,
+            )),
+            Some("comment-out") => Some(Subcommand::CommentOut(
+                args.free_from_str()
+                    .map_err(|_| "`comment-out` requires a `KEY` argument")?,
+            )),
+            Some("uncomment") => Some(Subcommand::Uncomment(
+                args.free_from_str()
+                    .map_err(|_| "`uncomment` requires a `KEY` argument")?,
+            )),
+            Some("lint") => Some(Subcommand::Lint),
+            Some("compare") => Some(Subcommand::Compare),
+            Some("encrypt") => Some(Subcommand::Encrypt),
+            Some(x) => return Err(format!("Unknown subcommand - `{}`", x)),
+            None => None,
+        };
+
         // It's up to the caller what to do with the remaining arguments.
         let remaining = args.finish();
         if !remaining.is_empty() {
@@ -63,45 +549,246 @@ impl Cli {
         Ok(res)
     }
 
-    pub fn path(&self) -> Result<&str, &str> {
-        let path = self.path.as_ref().ok_or("-f/--file option is required")?;
+    // Returns the index of the `--`, or of the first argument which is neither an option nor the
+    // value of an option if that comes first. So a command which starts with `-` still needs the
+    // `--`, while a `--` after the command is one of its arguments.
+    fn command_start(args: &[OsString]) -> Option<usize> {
+        let mut args = args.iter().map(|x| x.to_string_lossy()).enumerate();
+
+        while let Some((idx, arg)) = args.next() {
+            // A `--` after the command belongs to the command i.e. `npm run dev -- --port 3000`
+            if arg == "--" {
+                return Some(idx);
+            }
+
+            if !arg.starts_with('-') {
+                // Subcommands take their own argument, which is not a command
+                if idx == 0 && SUBCOMMANDS.contains(&arg.as_ref()) {
+                    return args.find(|(_, x)| x == "--").map(|(idx, _)| idx);
+                }
+
+                return Some(idx);
+            }
+
+            if VALUE_OPTIONS.contains(&arg.as_ref()) {
+                args.next();
+            }
+        }
 
-        Ok(path)
+        None
+    }
+
+    // Returns the only file, for the subcommands which edit the file
+    pub fn file(&self) -> Result<&String, &str> {
+        match self.paths.as_slice() {
+            [path] => Ok(path),
+            _ => Err("Exactly one -f/--file is required"),
+        }
+    }
+
+    // `ZENV_KEY` is ignored without the `crypto` feature, while --decrypt-key is an error
+    pub fn key(&self) -> Result<Option<[u8; 32]>, String> {
+        self.decrypt_key
+            .clone()
+            .or_else(|| {
+                env::var("ZENV_KEY")
+                    .ok()
+                    .filter(|x| cfg!(feature = "crypto") && !x.is_empty())
+            })
+            .map(|x| crypto::key(&x))
+            .transpose()
+    }
+
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            heredoc: self.heredoc,
+            hash_comments_require_space: self.hash_comments_require_space,
+            strict: !self.lenient,
+            allow_brackets: self.allow_brackets,
+            relaxed_keys: self.relaxed_keys,
+            conditionals: self.conditionals,
+            line_continuation: self.line_continuation,
+            keep_quotes: self.keep_quotes,
+            allow_quoted_keys: self.allow_quoted_keys,
+        }
+    }
+
+    pub fn format(&self) -> Result<Option<Format>, &str> {
+        // The flags are just the shortcuts of --format
+        let flags = [
+            (self.print, Format::Dotenv),
+            (self.export, Format::Export),
+            (self.json, Format::Json),
+            (self.docker_args, Format::Docker),
+        ];
+
+        let mut formats = self
+            .format
+            .into_iter()
+            .chain(self.print_shell)
+            .chain(flags.iter().filter(|x| x.0).map(|x| x.1));
+
+        match (formats.next(), formats.next()) {
+            (None, _) if self.print_effective => Ok(Some(Format::Dotenv)),
+            (format, None) => Ok(format),
+            _ => {
+                Err("--format, --print-shell, --print, --export, --json and --docker-args can't be used together")
+            }
+        }
     }
 
     pub fn binary(&self) -> Result<&OsString, &str> {
         self.binary.as_ref().ok_or("<binary> name is required")
     }
 
-    pub fn help_doc() -> String {
+    pub fn help_doc(colored: bool) -> String {
+        let h = |x| color::header(x, colored);
+
         format!(
             "\
 {name} {ver}
 {desc}
 
-USAGE:
-    {name} [FLAGS] [OPTIONS] -- <binary> [args]...
+{usage}
+    {name} [FLAGS] [OPTIONS] [--] <binary> [args]...
+    {name} [FLAGS] [OPTIONS] --parallel [--] <command>...
+    {name} [FLAGS] [OPTIONS] --format <dotenv|export|json|yaml|docker|fish|powershell>
+    {name} [FLAGS] [OPTIONS] --template <file> [--out <file>]
+    {name} [FLAGS] [OPTIONS] <SUBCOMMAND>
 
-FLAGS:
+{flags}
     -v, --version       Prints version
     -h, --help          Prints help information
     -x, --expand        Enable variable expansion
+    --expand-args       Substitute the variables in the command and its arguments i.e. ${{VAR}}
+    --emit-env-file     Write the variables to a temporary file as KEY=value, and replace
+                        {{envfile}} in the command and its arguments with its path. Removed
+                        after the command
+    --parallel          Run every argument as a separate command, all sharing the same
+                        variables. Fails if any of the commands fails
+    --verbose           Print a summary to stderr before running the command, and the
+                        warnings i.e. dropped lines, duplicate keys and overrides
+    --lenient           Skip the invalid lines instead of failing
+    --allow-brackets    Allow indexed keys i.e. KEY[0], which are passed as they are
+    --relaxed-keys      Allow `.`, `-` and `/` in the keys i.e. spring.datasource.url
+    --allow-quoted-keys Allow quoted keys i.e. \"weird key\"=value, which are taken as they are
+    --conditionals      Enable conditional blocks i.e. # zenv:if KEY=value ... # zenv:endif
+    --line-continuation Continue the unquoted values ending with `\\` on the next line
+    --keep-quotes       Keep the quotes around the values i.e. KEY=\"value\" is \"value\", only
+                        meant for the programs which expect the literal quotes
+    --heredoc           Enable heredoc style values i.e. KEY<<EOF
+    --no-env-fallback   Don't substitute variables from the running process, so they are empty
+                        and listed by --print-missing
+    --require-nonempty  Fail if the files don't have any variable i.e. an empty file
+    --strict            Make `compare` fail if any variable differs or is unset
+    --fail-on-override  Fail if any variable would replace a different inherited value i.e. PATH
+    --warn-on-override  Same as --fail-on-override, but only print a warning to stderr
+    --print             Same as --format dotenv
+    --export            Same as --format export
+    --json              Same as --format json
+    --docker-args       Same as --format docker
+    --print-effective   Print the whole environment of the command i.e. with the inherited
+                        variables, can be combined with --format
+    --print-missing     Print the variables which are referenced by the substitution but are
+                        not defined anywhere, with the line, instead of running a command
+    --sort              Sort the printed variables by the key, instead of the file order
+    --print-header      Print the leading comment block of the first file i.e. its description
+                        before the variables, except with the json and docker formats
+    --escape-newlines   Keep each variable on one line in the shell formats, i.e. with
+                        `$'...'` for --export, dotenv always does
+
+{options}
+    --format            Print the variables instead of running a command, one of dotenv,
+                        export, json, yaml, docker i.e. -e 'KEY=value' for `docker run`,
+                        fish or powershell
+    --print-shell       Same as --format, with the export syntax of the shell, one of bash,
+                        fish or powershell
+    -f, --file          Path to .env file, can be repeated. Later files take precedence. A
+                        leading ~/ is the home directory
+    --expect-sha256     Expected SHA-256 of the -f/--file file in the same position, fails
+                        if the content is different. Given once for each file
+    --decrypt-key       Key of the encrypted -f/--file files as 64 hex digits, otherwise taken
+                        from ZENV_KEY. Requires the `crypto` feature
+    --url               Download the .env file from an https:// URL, can be repeated. Loaded
+                        after the -f/--file files, requires the `http` feature
+    --toml              Load the string values of the [env] table of a TOML file, can be repeated.
+                        Loaded after the --url files, requires the `toml` feature
+    --hash-comments-require-space <true|false>
+                        Whether `#` only starts a comment after a whitespace [default: true]
+    --interpolate-from  Only use the variables of this file for the substitution, without
+                        passing them to the command. Can be repeated
+    --set <KEY=value>   Set a variable which overrides the files, can be repeated
+    --append-path <KEY=dir>
+                        Add the directory at the end of a PATH-like variable, separated by `:`
+                        or `;` on Windows. Uses the inherited value if the files don't define
+                        it, or creates it. Can be repeated
+    --prepend-path <KEY=dir>
+                        Same as --append-path, but adds the directory at the start
+    --require           Variable which must be defined and not empty, can be repeated
+    --only              Only pass or print the variables of the files which match the pattern,
+                        where `*` and `?` are wildcards i.e. DB_*. Can be repeated
+    --unset             Remove the variables which match the pattern, also the inherited ones.
+                        Can be repeated
+    --add-prefix        Add this prefix to every key of the files i.e. MYAPP_ turns PORT into
+                        MYAPP_PORT. Done after --only and --unset
+    --max-var-size      Fail if any value is larger than this, in bytes
+    --max-env-size      Fail if all the variables as KEY=value are larger than this, in bytes
+    --inherit           Only pass this variable from the current environment, can be repeated
+    --template          Substitute the variables in this file and print it, instead of running
+                        a command. Variables which are not found are replaced with nothing
+    --out               Write the substituted --template to this file, instead of stdout
+    --pid-file          Write the PID of the command to this file, removed after it exits
+    --on-spawn-error    What to do if the command is not found, one of fail or ignore. Ignore
+                        only prints a warning, and exits with 0 if nothing else fails [default: fail]
+    --retry             Run the command again this many times if it fails i.e. a non-zero
+                        exit code, and exit with the last code [default: 0]
+    --retry-delay       Time to wait before each retry i.e. 500ms, 2s or 1m [default: 1s]
+    --limit-nofile      Maximum number of open files of the command, only on Unix
+    --limit-memory      Maximum address space of the command in bytes, only on Unix
+    --color             When to color the help and the errors, one of auto, always or never.
+                        auto disables them if NO_COLOR is set or not a terminal [default: auto]
+    --compat            Rules of the substitution, one of zenv or bash [default: zenv]
+    --expand-sigil      Character which starts a variable. Any other than $ encloses the name
+                        instead i.e. %VAR% or @VAR@, where %% is a literal % [default: $]
+    --encoding          Encoding of the files, one of utf8, latin1 or lossy [default: utf8]
+    --key-transform     Normalize the keys, one of upper, lower or none [default: none]
 
-OPTIONS:
-    -f, --file          Path to .env file
+{subcommands}
+    set <KEY=value>     Update the value of the key in the file, or add it if missing
+    get <KEY>           Print the value of the key, fails if the key is not defined
+    comment-out <KEY>   Comment out the lines which define the key
+    uncomment <KEY>     Uncomment the lines which define the key
+    lint                Report the errors and the warnings of the files as file:line: message,
+                        fails if there is any error
+    compare             Print whether the current environment has the same value as the files,
+                        a different value or is unset, for each key. The values aren't printed
+    encrypt             Print the only -f/--file file encrypted with --decrypt-key, or write it
+                        to --out
 
-ARGS:
-    <binary>            Command that needs to be executed
+{args}
+    <binary>            Command that needs to be executed. Without `--`, the first argument
+                        which is not an option is the command
     [args]...           Arguments for the command
 
-Examples:
+{examples}
     {name} -f .env -- node index.js
     {name} -f .env -- npm run dev
     {name} -f .env -- terraform apply
+    {name} -f .env --parallel \"node api.js\" \"node worker.js\"
+    {name} set -f .env PORT=8080
+    {name} get -f .env -x DATABASE_URL
+    {name} encrypt -f .env --decrypt-key \"$KEY\" --out .env.vault
+    eval \"$({name} -f .env --export --escape-newlines)\"
 ",
             name = NAME,
             ver = VERSION,
             desc = DESC,
+            usage = h("USAGE:"),
+            flags = h("FLAGS:"),
+            options = h("OPTIONS:"),
+            subcommands = h("SUBCOMMANDS:"),
+            args = h("ARGS:"),
+            examples = h("Examples:"),
         )
     }
 }
@@ -0,0 +1,67 @@
+use std::{env, io::IsTerminal, str::FromStr};
+
+const RESET: &str = "\x1b[0m";
+const HEADER: &str = "\x1b[1;33m";
+const ERROR: &str = "\x1b[1;31m";
+
+/// When to use the ANSI colors in the help and the errors
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Color {
+    /// Only if the stream is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+
+    /// Always, even if the output is piped
+    Always,
+
+    /// Never
+    Never,
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            x => Err(format!(
+                "Invalid color `{}`, expected one of auto, always or never",
+                x
+            )),
+        }
+    }
+}
+
+impl Color {
+    // Whether the colors should be written to the stream
+    pub fn enabled(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            // https://no-color.org, only a non-empty value disables the colors
+            Self::Auto => {
+                env::var_os("NO_COLOR").map_or(true, |x| x.is_empty()) && stream.is_terminal()
+            }
+        }
+    }
+}
+
+fn paint(code: &str, text: &str, enabled: bool) -> String {
+    if enabled {
+        format!("{}{}{}", code, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+// Section headers of the help i.e. `USAGE:`
+pub fn header(text: &str, enabled: bool) -> String {
+    paint(HEADER, text, enabled)
+}
+
+// Prefix of the error messages
+pub fn error(text: &str, enabled: bool) -> String {
+    paint(ERROR, text, enabled)
+}
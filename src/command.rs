@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    env,
+    ffi::{OsStr, OsString},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+const WINDOWS_EXTENSIONS: [&str; 3] = ["exe", "cmd", "bat"];
+
+/// Returns the names to try, in order, when spawning `binary` on Windows. Commands like `npm`
+/// are actually `npm.cmd` which are not found without the extension, so the common
+/// executable extensions are tried after the name itself. If the name already has an
+/// extension then only the name is returned.
+///
+/// Example
+/// ```
+/// use std::ffi::OsStr;
+///
+/// let names = zenv::exe_candidates(OsStr::new("npm"));
+///
+/// assert_eq!(names, vec!["npm", "npm.exe", "npm.cmd", "npm.bat"]);
+///
+/// assert_eq!(zenv::exe_candidates(OsStr::new("npm.cmd")), vec!["npm.cmd"]);
+/// ```
+pub fn exe_candidates(binary: &OsStr) -> Vec<OsString> {
+    let mut names = vec![binary.to_os_string()];
+
+    if Path::new(binary).extension().is_none() {
+        for ext in WINDOWS_EXTENSIONS.iter() {
+            let mut name = binary.to_os_string();
+            name.push(".");
+            name.push(ext);
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+/// Options to control the environment of the command built by [`build_command`]
+#[derive(Debug, Clone, Default)]
+pub struct CommandOptions {
+    /// Variables which are copied from the current environment. If it's empty, the whole
+    /// environment is inherited, otherwise the command starts from an empty environment with only
+    /// these variables. The variables of the file always take precedence. Defaults to empty.
+    pub inherit: Vec<String>,
+
+    /// Variables which are removed from the environment of the command, even if they are
+    /// inherited. Defaults to empty.
+    pub unset: Vec<String>,
+}
+
+/// Builds the [`Command`] to run `binary` with the variables, the same way as the `zenv` binary
+/// does, without spawning it. The stdio is inherited from the current process. The returned
+/// command can be customized further, like setting the working directory, before spawning it.
+///
+/// Example
+/// ```
+/// use std::{collections::HashMap, ffi::OsStr};
+///
+/// use zenv::{build_command, CommandOptions};
+///
+/// let vars: HashMap<_, _> = vec![("PORT".to_string(), "5000".to_string())]
+///     .into_iter()
+///     .collect();
+///
+/// let opts = CommandOptions {
+///     inherit: vec!["PATH".to_string()],
+///     ..Default::default()
+/// };
+///
+/// let mut cmd = build_command(OsStr::new("node"), &["server.js".into()], &vars, &opts);
+///
+/// cmd.current_dir("/tmp");
+///
+/// let envs: Vec<_> = cmd.get_envs().filter(|(k, _)| k == &"PORT").collect();
+///
+/// assert_eq!(envs, vec![(OsStr::new("PORT"), Some(OsStr::new("5000")))]);
+/// ```
+pub fn build_command(
+    binary: &OsStr,
+    args: &[OsString],
+    vars: &HashMap<String, String>,
+    opts: &CommandOptions,
+) -> Command {
+    let mut cmd = Command::new(binary);
+
+    // Start from an empty environment and only copy the listed variables,
+    // which can still be overridden by the file
+    if !opts.inherit.is_empty() {
+        cmd.env_clear();
+
+        for key in &opts.inherit {
+            if let Some(val) = env::var_os(key) {
+                cmd.env(key, val);
+            }
+        }
+    }
+
+    for key in &opts.unset {
+        cmd.env_remove(key);
+    }
+
+    cmd.args(args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .envs(vars);
+
+    cmd
+}
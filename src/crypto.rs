@@ -0,0 +1,119 @@
+// Encrypted .env files, which are a single header line followed by the hex of the 12 byte nonce,
+// the ciphertext and the 16 byte tag of AES-256-GCM. The header is also the associated data, so
+// it can't be swapped for another version.
+//
+//     ZENV-AES-256-GCM-V1
+//     <hex of nonce + ciphertext + tag>
+
+const HEADER: &str = "ZENV-AES-256-GCM-V1";
+
+// Whether the content is an encrypted file, regardless of the `crypto` feature
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes
+        .strip_prefix(HEADER.as_bytes())
+        .is_some_and(|x| x.starts_with(b"\n") || x.starts_with(b"\r\n"))
+}
+
+// 32 bytes as 64 hex digits, i.e. generated with `openssl rand -hex 32`
+#[cfg(feature = "crypto")]
+pub fn key(x: &str) -> Result<[u8; 32], String> {
+    let mut key = [0; 32];
+
+    match from_hex(x.trim()) {
+        Some(bytes) if bytes.len() == key.len() => {
+            key.copy_from_slice(&bytes);
+            Ok(key)
+        }
+        _ => Err("Invalid key, expected 64 hex digits i.e. `openssl rand -hex 32`".to_string()),
+    }
+}
+
+#[cfg(not(feature = "crypto"))]
+pub fn key(_: &str) -> Result<[u8; 32], String> {
+    Err("--decrypt-key requires the `crypto` feature".to_string())
+}
+
+#[cfg(feature = "crypto")]
+pub fn encrypt(plain: &[u8], key: &[u8; 32]) -> Result<String, String> {
+    use aes_gcm::{
+        aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+        Aes256Gcm,
+    };
+
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let sealed = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plain,
+                aad: HEADER.as_bytes(),
+            },
+        )
+        .map_err(|_| "Unable to encrypt the file".to_string())?;
+
+    let body: String = nonce
+        .iter()
+        .chain(&sealed)
+        .map(|x| format!("{:02x}", x))
+        .collect();
+
+    Ok(format!("{}\n{}\n", HEADER, body))
+}
+
+#[cfg(not(feature = "crypto"))]
+pub fn encrypt(_: &[u8], _: &[u8; 32]) -> Result<String, String> {
+    Err("`encrypt` requires the `crypto` feature".to_string())
+}
+
+// The tag is checked, so a wrong key or a modified file is an error instead of garbage
+#[cfg(feature = "crypto")]
+pub fn decrypt(bytes: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    use aes_gcm::{
+        aead::{Aead, KeyInit, Payload},
+        Aes256Gcm, Nonce,
+    };
+
+    const NONCE_LEN: usize = 12;
+
+    let invalid = || "Invalid encrypted file".to_string();
+
+    let body = std::str::from_utf8(&bytes[HEADER.len()..]).map_err(|_| invalid())?;
+    let body = from_hex(body.trim()).ok_or_else(invalid)?;
+
+    if body.len() < NONCE_LEN {
+        return Err(invalid());
+    }
+
+    let (nonce, sealed) = body.split_at(NONCE_LEN);
+
+    Aes256Gcm::new(key.into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: sealed,
+                aad: HEADER.as_bytes(),
+            },
+        )
+        .map_err(|_| {
+            "Unable to decrypt the file, the key is wrong or the file is modified".to_string()
+        })
+}
+
+#[cfg(not(feature = "crypto"))]
+pub fn decrypt(_: &[u8], _: &[u8; 32]) -> Result<Vec<u8>, String> {
+    Err("Encrypted files require the `crypto` feature".to_string())
+}
+
+#[cfg(feature = "crypto")]
+fn from_hex(x: &str) -> Option<Vec<u8>> {
+    if x.len() % 2 != 0 || !x.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..x.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&x[i..i + 2], 16).ok())
+        .collect()
+}
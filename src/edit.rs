@@ -0,0 +1,214 @@
+use std::{fs, io, path::Path};
+
+use zenv::{Line, Lines, ParseOptions, Quote};
+
+use crate::format;
+
+const LF: char = '\n';
+
+// Sets the `KEY=value` pair in the file, which is created if it doesn't exist yet
+pub fn set(path: &Path, pair: &str, opts: &ParseOptions) -> Result<(), String> {
+    let (key, val) = match pair.split_once('=') {
+        Some((k, v)) if !k.trim().is_empty() => (k.trim(), v),
+        _ => return Err(format!("Expected `KEY=value` - `{}`", pair)),
+    };
+
+    let src = read(path)?;
+    let out = upsert(&src, key, val, opts)?;
+
+    write(path, &out, eol(&src))
+}
+
+// Comments out every definition of the key, or uncomments them. Every line of a multi-line
+// definition i.e. a heredoc, is changed. Returns the number of the changed definitions, and the
+// file is left untouched if there are none.
+pub fn toggle(path: &Path, key: &str, comment: bool, opts: &ParseOptions) -> Result<usize, String> {
+    let src = read(path)?;
+    let mut lines: Vec<String> = src.lines().map(String::from).collect();
+
+    // Also the definitions inside the conditional blocks, without failing on the other lines
+    let opts = ParseOptions {
+        strict: false,
+        conditionals: false,
+        ..opts.clone()
+    };
+
+    // The comments are parsed without the `#`, so that the other lines are not found
+    let text = if comment {
+        src.clone()
+    } else {
+        lines
+            .iter()
+            .map(|x| uncomment(x).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let parsed = Lines::parse(&text, &opts).map_err(|e| e.to_string())?;
+
+    // Line numbers start from 1
+    let ranges: Vec<_> = parsed
+        .entries()
+        .filter(|x| x.key == key)
+        .map(|x| x.line - 1..x.end)
+        .filter(|x| comment || lines[x.clone()].iter().all(|x| uncomment(x).is_some()))
+        .collect();
+
+    for range in &ranges {
+        for (i, idx) in range.clone().enumerate() {
+            let line = &lines[idx];
+
+            lines[idx] = match (comment, line.trim_start().strip_prefix('#')) {
+                (true, _) => format!("# {}", line),
+                // Only the first line was indented by `comment-out`
+                (false, Some(x)) if i == 0 => x.trim_start().to_string(),
+                (false, Some(x)) => x.strip_prefix(' ').unwrap_or(x).to_string(),
+                (false, None) => continue,
+            };
+        }
+    }
+
+    if !ranges.is_empty() {
+        lines.push(String::new());
+        write(path, &lines.join("\n"), eol(&src))?;
+    }
+
+    Ok(ranges.len())
+}
+
+// Content of the comment, if the line is a comment
+fn uncomment(line: &str) -> Option<&str> {
+    line.trim_start().strip_prefix('#').map(str::trim_start)
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    match fs::read_to_string(path) {
+        Ok(src) => Ok(src),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("Unable to read the file - {}", e)),
+    }
+}
+
+// Updates the value of the key, keeping its position and quote style, or appends it at the end.
+// Everything else in the file i.e. comments and blank lines, is kept as it is.
+pub fn upsert(src: &str, key: &str, val: &str, opts: &ParseOptions) -> Result<String, String> {
+    let mut lines: Vec<String> = src.lines().map(String::from).collect();
+
+    let unwritable = || {
+        format!(
+            "Unable to write `{}`, the value can't be represented in a .env file",
+            key
+        )
+    };
+
+    // Only the last definition is effective, so that's the one to update
+    let found = lines.iter().enumerate().rev().find_map(|(i, line)| {
+        let q = match Line::parse(line, opts) {
+            Line::KeyVal(kv) | Line::Append(kv) if kv.k == key => kv.q,
+            _ => return None,
+        };
+
+        // The key is kept as it is written i.e. quoted, while `+=` is replaced. Whatever follows
+        // the value, like a comment, is kept after the new value.
+        let (head, raw) = line.split_once('=').unwrap_or((key, ""));
+        let head = head.strip_suffix('+').unwrap_or(head);
+        let rest = trailing(raw, &q, opts.hash_comments_require_space);
+
+        Some((i, head.to_string(), q, rest.to_string()))
+    });
+
+    match found {
+        Some((i, head, q, rest)) => {
+            let val = quote(val, &q).ok_or_else(unwritable)?;
+            lines[i] = format!("{}={}{}", head, val, rest);
+        }
+        None => {
+            let val = quote(val, &Quote::No).ok_or_else(unwritable)?;
+            lines.push(format!("{}={}", key, val));
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push(LF);
+
+    // Making sure that the file reads back the same value
+    let written = Lines::parse(&out, opts)
+        .map_err(|e| e.to_string())?
+        .to_vec();
+
+    match written.iter().rev().find(|(k, _)| k == key) {
+        Some((_, v)) if v == val => Ok(out),
+        _ => Err(unwritable()),
+    }
+}
+
+// Uses the same quote as before, if it can hold the value
+fn quote(val: &str, q: &Quote) -> Option<String> {
+    match q {
+        Quote::Single if !val.contains(['\'', LF]) => Some(format!("'{}'", val)),
+        Quote::Backtick if !val.contains(['`', LF]) => Some(format!("`{}`", val)),
+        Quote::Double if !val.contains('"') => Some(format::double_quoted(val)),
+        _ => format::dotenv(val),
+    }
+}
+
+// Returns the content after the value i.e. the comment
+fn trailing<'r>(raw: &'r str, q: &Quote, require_space: bool) -> &'r str {
+    let close = match q {
+        Quote::Single => '\'',
+        Quote::Double => '"',
+        Quote::Backtick => '`',
+        Quote::No => {
+            let idx = raw.char_indices().find(|(i, c)| {
+                let comment = raw[*i..].trim_start().starts_with('#');
+                comment && (c.is_whitespace() || (!require_space && *c == '#'))
+            });
+
+            return idx.map_or("", |(i, _)| &raw[i..]);
+        }
+    };
+
+    raw.char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == close)
+        .map_or("", |(i, _)| &raw[i + 1..])
+}
+
+// Line ending of the file, which is kept while writing
+fn eol(src: &str) -> &'static str {
+    if src.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+// Writes to a temporary file first, so that the file is never left half written. The `content`
+// has `\n` as the line ending, which is replaced with `eol`. The permissions of the existing file
+// are kept i.e. 0600 for the secrets, as the temporary file replaces it.
+fn write(path: &Path, content: &str, eol: &str) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".zenv-tmp");
+
+    let content = match eol {
+        "\n" => content.to_string(),
+        eol => content.replace('\n', eol),
+    };
+
+    let perms = match fs::metadata(path) {
+        Ok(meta) => Some(meta.permissions()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Unable to write the file - {}", e)),
+    };
+
+    fs::write(&tmp, content)
+        .and_then(|_| match perms {
+            Some(perms) => fs::set_permissions(&tmp, perms),
+            None => Ok(()),
+        })
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| {
+            fs::remove_file(&tmp).ok();
+            format!("Unable to write the file - {}", e)
+        })
+}
@@ -0,0 +1,61 @@
+use std::{
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
+
+/// How the bytes of the file are decoded into text, see [`Zenv::encoding`](crate::Zenv::encoding)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Encoding {
+    /// The file must be valid UTF-8, otherwise reading fails
+    #[default]
+    Utf8,
+
+    /// Every byte is a character i.e. ISO-8859-1, so decoding never fails
+    Latin1,
+
+    /// UTF-8, but the invalid sequences are replaced with `U+FFFD`
+    Lossy,
+}
+
+impl Encoding {
+    /// Decodes the bytes into a string
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Encoding;
+    ///
+    /// let bytes = b"NAME=Jos\xe9";
+    ///
+    /// assert!(Encoding::Utf8.decode(bytes.to_vec()).is_err());
+    /// assert_eq!(Encoding::Latin1.decode(bytes.to_vec()).unwrap(), "NAME=José");
+    /// assert_eq!(Encoding::Lossy.decode(bytes.to_vec()).unwrap(), "NAME=Jos\u{FFFD}");
+    /// ```
+    pub fn decode(&self, bytes: Vec<u8>) -> Result<String> {
+        match self {
+            Self::Utf8 => String::from_utf8(bytes).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The file is not valid UTF-8 - {}", e.utf8_error()),
+                )
+            }),
+            Self::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+            Self::Lossy => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "utf8" => Ok(Self::Utf8),
+            "latin1" => Ok(Self::Latin1),
+            "lossy" => Ok(Self::Lossy),
+            x => Err(format!(
+                "Invalid encoding `{}`, expected one of utf8, latin1 or lossy",
+                x
+            )),
+        }
+    }
+}
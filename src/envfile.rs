@@ -0,0 +1,60 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Placeholder in the arguments which is replaced with the path of the file
+pub const PLACEHOLDER: &str = "{envfile}";
+
+// Temporary file with the variables as `KEY=value`, which is removed when dropped
+pub struct EnvFile {
+    pub path: PathBuf,
+}
+
+impl EnvFile {
+    // Values are written as they are, like the tools i.e. `docker --env-file` read them, so the
+    // new lines can't be represented
+    pub fn create(vars: &[(&String, &String)]) -> Result<Self, String> {
+        let mut content = String::new();
+
+        for (k, v) in vars {
+            if v.contains('\n') {
+                return Err(format!(
+                    "Unable to write `{}` to the env file, the value has a new line",
+                    k
+                ));
+            }
+
+            content.push_str(&format!("{}={}\n", k, v));
+        }
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.subsec_nanos());
+
+        let path = env::temp_dir().join(format!("zenv-{}-{}.env", std::process::id(), nanos));
+
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create_new(true);
+
+        // Only readable by the current user, as it can have secrets
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+
+        let file = Self { path };
+
+        opts.open(&file.path)
+            .and_then(|mut x| x.write_all(content.as_bytes()))
+            .map_err(|e| format!("Unable to write the env file - {}", e))?;
+
+        Ok(file)
+    }
+}
+
+impl Drop for EnvFile {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
@@ -0,0 +1,343 @@
+use std::{fmt::Write, str::FromStr};
+
+const LF: char = '\n';
+
+/// Output representation of the variables when they are printed instead of spawning a program
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// `KEY=value`, can be read back as a .env file
+    Dotenv,
+
+    /// `export KEY='value'`, can be evaluated by a POSIX shell
+    Export,
+
+    /// A single JSON object
+    Json,
+
+    /// A YAML mapping, with every value double quoted and the keys quoted unless they are plain
+    Yaml,
+
+    /// `-e 'KEY=value'` flags on a single line, to be spliced into a `docker run` by a POSIX shell
+    Docker,
+
+    /// `set -gx KEY 'value'`, can be evaluated by fish
+    Fish,
+
+    /// `$env:KEY = 'value'`, can be evaluated by PowerShell
+    PowerShell,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dotenv" => Ok(Self::Dotenv),
+            "export" => Ok(Self::Export),
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "docker" => Ok(Self::Docker),
+            "fish" => Ok(Self::Fish),
+            "powershell" => Ok(Self::PowerShell),
+            x => Err(format!(
+                "Invalid format `{}`, expected one of dotenv, export, json, yaml, docker, fish or powershell",
+                x
+            )),
+        }
+    }
+}
+
+impl Format {
+    // Whether `#` starts a comment in the output, so that the header can be printed
+    pub fn has_comments(self) -> bool {
+        !matches!(self, Self::Json | Self::Docker)
+    }
+
+    // Export syntax of the shell, for --print-shell
+    pub fn shell(s: &str) -> Result<Self, String> {
+        match s {
+            "bash" => Ok(Self::Export),
+            "fish" => Ok(Self::Fish),
+            "powershell" => Ok(Self::PowerShell),
+            x => Err(format!(
+                "Invalid shell `{}`, expected one of bash, fish or powershell",
+                x
+            )),
+        }
+    }
+}
+
+pub struct Formatter {
+    pub format: Format,
+
+    // Whether to encode the new lines so that every variable is on a single line in the shell
+    // formats, the dotenv format always does
+    pub escape_newlines: bool,
+}
+
+impl Formatter {
+    pub fn render(&self, vars: &[(String, String)]) -> Result<String, String> {
+        let mut out = String::new();
+
+        match self.format {
+            Format::Dotenv => {
+                for (k, v) in vars {
+                    let v = dotenv(v).ok_or_else(|| {
+                        format!(
+                            "Unable to print `{}`, the value can't be represented in a .env file",
+                            k
+                        )
+                    })?;
+
+                    writeln!(out, "{}={}", k, v).ok();
+                }
+            }
+            Format::Export => {
+                for (k, v) in vars {
+                    writeln!(out, "export {}={}", k, self.export(v)).ok();
+                }
+            }
+            Format::Json => {
+                out.push('{');
+                for (i, (k, v)) in vars.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write!(out, "\n  {}: {}", json(k), json(v)).ok();
+                }
+                if !vars.is_empty() {
+                    out.push(LF);
+                }
+                out.push_str("}\n");
+            }
+            Format::Yaml => {
+                if vars.is_empty() {
+                    out.push_str("{}\n");
+                }
+                for (k, v) in vars {
+                    // JSON strings are valid YAML strings as well
+                    let k = match is_yaml_plain(k) {
+                        true => k.to_string(),
+                        false => json(k),
+                    };
+                    writeln!(out, "{}: {}", k, json(v)).ok();
+                }
+            }
+            Format::Docker => {
+                let flags: Vec<_> = vars
+                    .iter()
+                    .map(|(k, v)| format!("-e {}", self.export(&format!("{}={}", k, v))))
+                    .collect();
+
+                writeln!(out, "{}", flags.join(" ")).ok();
+            }
+            Format::Fish => {
+                for (k, v) in vars {
+                    writeln!(out, "set -gx {} {}", k, self.fish(v)).ok();
+                }
+            }
+            Format::PowerShell => {
+                for (k, v) in vars {
+                    writeln!(out, "{} = {}", powershell_var(k), self.powershell(v)).ok();
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn export(&self, v: &str) -> String {
+        if self.escape_newlines && v.contains(LF) {
+            return ansi_c(v);
+        }
+
+        let mut s = String::with_capacity(v.len() + 2);
+        s.push('\'');
+        for c in v.chars() {
+            match c {
+                '\'' => s.push_str("'\\''"),
+                c => s.push(c),
+            }
+        }
+        s.push('\'');
+        s
+    }
+
+    // Only `\\` and `\'` are escapes inside the single quotes of fish, so a new line is written
+    // as `\n` between the quotes, where it is an escape as well
+    fn fish(&self, v: &str) -> String {
+        let mut s = String::with_capacity(v.len() + 2);
+        s.push('\'');
+        for c in v.chars() {
+            match c {
+                '\\' => s.push_str("\\\\"),
+                '\'' => s.push_str("\\'"),
+                LF if self.escape_newlines => s.push_str("'\\n'"),
+                c => s.push(c),
+            }
+        }
+        s.push('\'');
+        s
+    }
+
+    // Single quotes are literal in PowerShell, except `''` which is a single `'`. The curly
+    // quotes are also quotes for it, so they are doubled as well.
+    fn powershell(&self, v: &str) -> String {
+        if self.escape_newlines && v.contains(LF) {
+            return powershell_double(v);
+        }
+
+        let mut s = String::with_capacity(v.len() + 2);
+        s.push('\'');
+        for c in v.chars() {
+            match c {
+                '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' => {
+                    s.push(c);
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+        s.push('\'');
+        s
+    }
+}
+
+// `$'...'` of bash, zsh and ksh where a new line can be `\n`, so the `\` and `'` are escaped
+fn ansi_c(v: &str) -> String {
+    let mut s = String::with_capacity(v.len() + 3);
+    s.push_str("$'");
+    for c in v.chars() {
+        match c {
+            '\\' => s.push_str("\\\\"),
+            '\'' => s.push_str("\\'"),
+            LF => s.push_str("\\n"),
+            c => s.push(c),
+        }
+    }
+    s.push('\'');
+    s
+}
+
+// Double quotes of PowerShell, where a new line can be `` `n ``. The backtick is the escape, and
+// the `$` and the straight and curly double quotes are escaped with it.
+fn powershell_double(v: &str) -> String {
+    let mut s = String::with_capacity(v.len() + 2);
+    s.push('"');
+    for c in v.chars() {
+        match c {
+            LF => s.push_str("`n"),
+            '`' | '$' | '"' | '\u{201c}' | '\u{201d}' | '\u{201e}' => {
+                s.push('`');
+                s.push(c);
+            }
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+// `$env:KEY`, or `${env:KEY}` for the keys which are not a plain name i.e. `spring.datasource.url`
+fn powershell_var(k: &str) -> String {
+    if k.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return format!("$env:{}", k);
+    }
+
+    let mut s = String::from("${env:");
+    for c in k.chars() {
+        if matches!(c, '{' | '}' | '`') {
+            s.push('`');
+        }
+        s.push(c);
+    }
+    s.push('}');
+    s
+}
+
+// Leading comment block of the file i.e. its description, till the first line which is not a
+// comment. The directives i.e. `# zenv:if` are not a part of it.
+pub fn header(src: &str) -> Vec<&str> {
+    src.lines()
+        .skip_while(|x| x.trim().is_empty())
+        .take_while(|x| {
+            let x = x.trim_start();
+            x.starts_with('#') && !x.starts_with("# zenv:")
+        })
+        .collect()
+}
+
+// Quoted so that it is read back as the same value, or `None` if no quote can hold it. The single
+// quotes and the backticks are taken literally, while the double quotes can have a new line as
+// `\n` but not a `"`, as there is no escape for it.
+pub fn dotenv(v: &str) -> Option<String> {
+    if !v.is_empty() && v.chars().all(is_safe) {
+        return Some(v.to_string());
+    }
+
+    if !v.contains(['\'', LF]) {
+        return Some(format!("'{}'", v));
+    }
+
+    if !v.contains('"') {
+        return Some(double_quoted(v));
+    }
+
+    if !v.contains(['`', LF]) {
+        return Some(format!("`{}`", v));
+    }
+
+    None
+}
+
+pub fn double_quoted(v: &str) -> String {
+    let mut s = String::with_capacity(v.len() + 2);
+    s.push('"');
+    for c in v.chars() {
+        match c {
+            '\\' => s.push_str("\\\\"),
+            LF => s.push_str("\\n"),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+fn is_safe(c: char) -> bool {
+    c.is_alphanumeric() || "_-.,/:@%+=".contains(c)
+}
+
+// Whether the key is read back as the same string without quotes, by the YAML 1.1 loaders as well
+// which read `ON`, `y` or `NULL` as a boolean or null, and the digits as a number
+fn is_yaml_plain(k: &str) -> bool {
+    const RESERVED: [&str; 9] = ["y", "n", "yes", "no", "true", "false", "on", "off", "null"];
+
+    let mut chars = k.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED.iter().any(|x| x.eq_ignore_ascii_case(k))
+}
+
+fn json(v: &str) -> String {
+    let mut s = String::with_capacity(v.len() + 2);
+    s.push('"');
+    for c in v.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            LF => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if c.is_control() => {
+                write!(s, "\\u{:04x}", c as u32).ok();
+            }
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
@@ -0,0 +1,38 @@
+const STAR: char = '*';
+const QUESTION: char = '?';
+
+// Whether the text matches the pattern, where `*` matches any number of characters and `?`
+// matches a single character. Everything else is matched literally.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+
+    // Position of the last `*` and the text it is matched till, to backtrack
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(&STAR) => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == QUESTION || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the `*` match one more character
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == STAR)
+}
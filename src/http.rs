@@ -0,0 +1,33 @@
+// Downloads the .env files, over `https://` with the certificates of webpki-roots or plain
+// `http://`. Any other scheme is an error.
+#[cfg(feature = "http")]
+pub fn get(url: &str) -> Result<String, String> {
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("Expected an https:// or http:// URL - {}", url));
+    }
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .user_agent("zenv")
+        .build();
+
+    let res = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(status, _) => format!("Unable to fetch {} - Status {}", url, status),
+        ureq::Error::Transport(e) => match e.message() {
+            Some(msg) => format!("Unable to fetch {} - {}: {}", url, e.kind(), msg),
+            None => format!("Unable to fetch {} - {}", url, e.kind()),
+        },
+    })?;
+
+    res.into_string()
+        .map_err(|e| format!("Unable to fetch {} - {}", url, e))
+}
+
+#[cfg(not(feature = "http"))]
+pub fn get(_: &str) -> Result<String, String> {
+    Err("--url requires the `http` feature".to_string())
+}
@@ -7,8 +7,12 @@
 //!
 //! Example
 //! ```
+//! # #[cfg(feature = "std")]
 //! use zenv::{zenv, Zenv};
 //!
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! # #[cfg(feature = "std")]
 //! fn main() {
 //!     Zenv::new(".env", false).configure().ok();
 //!     // is equivalent to
@@ -22,35 +26,398 @@
 //! }
 //! ```
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod command;
+#[cfg(feature = "std")]
+mod encoding;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
 mod parser;
+#[cfg(feature = "std")]
+mod warning;
 
+#[cfg(feature = "std")]
 use std::{
     collections::HashMap,
-    fs::read_to_string,
+    convert::TryFrom,
+    fs::{read, File},
     io::{Error, ErrorKind, Result},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+#[cfg(feature = "std")]
+use parser::Expander;
+
+#[cfg(feature = "std")]
+pub use command::{build_command, exe_candidates, CommandOptions};
+#[cfg(feature = "std")]
+pub use encoding::Encoding;
+#[cfg(all(feature = "mmap", unix))]
+pub use mmap::Mmap;
+#[cfg(feature = "std")]
+pub use warning::{Warning, WarningKind};
+
 // Just re-exporting to use as a standalone parser
-pub use parser::{KeyVal, Line, Lines, Quote};
+#[cfg(feature = "std")]
+pub use parser::substitute;
+pub use parser::{
+    lint, parse_value, Compat, Entry, ExpandOptions, KeyTransform, KeyVal, Line, Lines, Lint,
+    ParseError, ParseOptions, Quote,
+};
+
+/// Ordered `(key, value)` pairs of the variables
+#[cfg(feature = "std")]
+pub type Pairs = Vec<(String, String)>;
 
 /// Use this to load and configure the environment variables
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct Zenv {
-    path: PathBuf,
+    paths: Vec<PathBuf>,
+
+    // Content of the files which are already in memory, instead of on the disk
+    texts: HashMap<PathBuf, String>,
+
+    // Variables of the files which are already parsed i.e. from another format
+    pairs: HashMap<PathBuf, Pairs>,
+
+    // Files which are only used by the substitution
+    interpolate: Vec<PathBuf>,
+    vars: Pairs,
     expand: bool,
+    env_fallback: bool,
+    key_transform: KeyTransform,
+    parse_options: ParseOptions,
+    encoding: Encoding,
+    compat: Compat,
+    sigil: char,
 }
 
+#[cfg(feature = "std")]
 impl Zenv {
-    /// Create a new instance of Zenv with the provided file path
-    pub fn new(path: &str, expand: bool) -> Self {
+    /// Create a new instance of Zenv with the provided file path, which can be anything that
+    /// can be referenced as a [`Path`] i.e. `&str`, `String`, `&Path` or `PathBuf`
+    ///
+    /// Example
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// let zenv = zenv::Zenv::new(Path::new("tests/.env.basic"), false);
+    ///
+    /// assert_eq!(zenv.parse().unwrap().get("BASIC"), Some(&"basic".to_string()));
+    /// ```
+    pub fn new(path: impl AsRef<Path>, expand: bool) -> Self {
+        Self::with_path(path.as_ref().to_path_buf(), expand)
+    }
+
+    fn with_path(path: PathBuf, expand: bool) -> Self {
         Self {
-            path: PathBuf::from(path),
+            paths: vec![path],
+            texts: HashMap::new(),
+            pairs: HashMap::new(),
+            interpolate: Vec::new(),
+            vars: Vec::new(),
             expand,
+            env_fallback: true,
+            key_transform: KeyTransform::None,
+            parse_options: ParseOptions::default(),
+            encoding: Encoding::Utf8,
+            compat: Compat::Zenv,
+            sigil: '$',
         }
     }
 
-    /// Read and parse the file from provided path and returns a hashmap
+    /// Add another file which is loaded after the previous ones. Its variables take precedence
+    /// over the variables of the previous files, except `KEY+=val` which appends to the previous
+    /// value. See [`Lines::merge`]. While substituting, the earlier files can also reference the
+    /// variables of this one.
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.base", false)
+    ///     .file("tests/.env.local")
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("HOST"), Some(&"localhost".to_string()));
+    /// assert_eq!(parsed.get("DIRS"), Some(&"/usr/bin:/extra/bin".to_string()));
+    /// ```
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        self.paths.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Same as [Zenv::new], but the content of the file is already in memory i.e. downloaded.
+    /// The `name` is only used to refer to the file, in the errors and [Zenv::parse_with_source].
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::from_text("remote.env", "BASIC=basic", false)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("BASIC"), Some(&"basic".to_string()));
+    /// ```
+    pub fn from_text(name: impl AsRef<Path>, src: impl Into<String>, expand: bool) -> Self {
+        let name = name.as_ref().to_path_buf();
+        let mut zenv = Self::with_path(name.clone(), expand);
+
+        zenv.texts.insert(name, src.into());
+        zenv
+    }
+
+    /// Same as [Zenv::file], but the content of the file is already in memory, see
+    /// [Zenv::from_text]
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.base", false)
+    ///     .text("remote.env", "HOST=example.org")
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("HOST"), Some(&"example.org".to_string()));
+    /// assert_eq!(parsed.get("PORT"), Some(&"80".to_string()));
+    /// ```
+    pub fn text(mut self, name: impl AsRef<Path>, src: impl Into<String>) -> Self {
+        let name = name.as_ref().to_path_buf();
+
+        self.paths.push(name.clone());
+        self.texts.insert(name, src.into());
+        self
+    }
+
+    /// Same as [Zenv::new], but the variables of the file are already parsed i.e. from another
+    /// format. The values are taken literally, without any substitution, like [Zenv::var]. The
+    /// `name` is only used to refer to the file, same as [Zenv::from_text].
+    ///
+    /// Example
+    /// ```
+    /// let pairs = vec![("HOST".to_string(), "${NOT_EXPANDED}".to_string())];
+    ///
+    /// let parsed = zenv::Zenv::from_pairs("Config.toml", pairs, true)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("HOST"), Some(&"${NOT_EXPANDED}".to_string()));
+    /// ```
+    pub fn from_pairs(
+        name: impl AsRef<Path>,
+        pairs: impl IntoIterator<Item = (String, String)>,
+        expand: bool,
+    ) -> Self {
+        let name = name.as_ref().to_path_buf();
+        let mut zenv = Self::with_path(name.clone(), expand);
+
+        zenv.pairs.insert(name, pairs.into_iter().collect());
+        zenv
+    }
+
+    /// Same as [Zenv::file], but the variables of the file are already parsed, see
+    /// [Zenv::from_pairs]
+    ///
+    /// Example
+    /// ```
+    /// let pairs = vec![("HOST".to_string(), "example.org".to_string())];
+    ///
+    /// let parsed = zenv::Zenv::new("tests/.env.base", false)
+    ///     .pairs("Config.toml", pairs)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("HOST"), Some(&"example.org".to_string()));
+    /// assert_eq!(parsed.get("PORT"), Some(&"80".to_string()));
+    /// ```
+    pub fn pairs(
+        mut self,
+        name: impl AsRef<Path>,
+        pairs: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let name = name.as_ref().to_path_buf();
+
+        self.paths.push(name.clone());
+        self.pairs.insert(name, pairs.into_iter().collect());
+        self
+    }
+
+    /// Add a file whose variables are only used by the substitution, like the secrets which are
+    /// referenced by the other files but should not be a part of the result themselves. Its values
+    /// are taken as they are, without any substitution. While substituting, a variable is looked
+    /// up in the files first, then in these files where the later ones take precedence, and then
+    /// in the running process, see [Zenv::env_fallback].
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.local", true)
+    ///     .interpolate_from("tests/.env.base")
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("URL"), Some(&"http://localhost:80".to_string()));
+    /// assert_eq!(parsed.get("PORT"), None);
+    /// ```
+    pub fn interpolate_from(mut self, path: impl AsRef<Path>) -> Self {
+        self.interpolate.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set a variable which takes precedence over the variables of every file. The value is taken
+    /// literally, without any substitution, but it is used while substituting the variables of
+    /// the files. So a variable is looked up in this order: these variables, later files, earlier
+    /// files and then the running process, see [Zenv::env_fallback].
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.base", true)
+    ///     .file("tests/.env.local")
+    ///     .var("HOST", "example.org")
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("URL"), Some(&"http://example.org:80".to_string()));
+    /// ```
+    pub fn var(mut self, key: &str, val: &str) -> Self {
+        self.vars.push((key.to_string(), val.to_string()));
+        self
+    }
+
+    /// Whether to do the variable substitution, same as the `expand` argument of [`Zenv::new`].
+    /// This is mostly useful after `TryFrom`, which creates the instance without the substitution.
+    ///
+    /// Example
+    /// ```
+    /// use std::{convert::TryFrom, path::Path};
+    ///
+    /// let parsed = zenv::Zenv::try_from(Path::new("tests/.env.expanded"))
+    ///     .unwrap()
+    ///     .substitution(true)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("EXPANDED_NEW"), Some(&"basic_expanded".to_string()));
+    /// ```
+    pub fn substitution(mut self, expand: bool) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    /// Whether to fallback to the variables of the running process while doing substitution.
+    /// Enabled by default. Disable this to make the substitution only depend on the file itself.
+    ///
+    /// A variable which is not found is substituted with an empty string, whether or not the
+    /// fallback is enabled, so it is never an error. With the fallback disabled, the variables
+    /// of the running process are not found either, and are listed by [`Zenv::missing`]. To
+    /// fail on a missing variable, check that [`Zenv::missing`] is empty before using the values.
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.expanded", true)
+    ///     .env_fallback(false)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("SYSTEM_VAR"), Some(&"_exist".to_string()))
+    /// ```
+    pub fn env_fallback(mut self, env_fallback: bool) -> Self {
+        self.env_fallback = env_fallback;
+        self
+    }
+
+    /// Set the rules of the variable substitution, see [`ExpandOptions::compat`]
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Compat, Zenv};
+    ///
+    /// let parsed = Zenv::new("tests/.env.expanded", true)
+    ///     .compat(Compat::Bash)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("EXPANDED"), Some(&"basic-expanded".to_string()))
+    /// ```
+    pub fn compat(mut self, compat: Compat) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Set the character which starts a variable, see [`ExpandOptions::sigil`]
+    ///
+    /// Example
+    /// ```
+    /// let zenv = zenv::Zenv::new("tests/.env.local", false).sigil('%');
+    ///
+    /// assert_eq!(zenv.render("HOST=%HOST%").unwrap(), "HOST=localhost");
+    /// ```
+    pub fn sigil(mut self, sigil: char) -> Self {
+        self.sigil = sigil;
+        self
+    }
+
+    /// Normalize the case of the keys after parsing, before the substitution. So the references
+    /// inside the values should use the transformed keys. Keys which collide after the
+    /// transformation are resolved by keeping the last one.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{KeyTransform, Zenv};
+    ///
+    /// let parsed = Zenv::new("tests/.env.basic", false)
+    ///     .key_transform(KeyTransform::Lower)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("basic"), Some(&"basic".to_string()))
+    /// ```
+    pub fn key_transform(mut self, key_transform: KeyTransform) -> Self {
+        self.key_transform = key_transform;
+        self
+    }
+
+    /// Set the [`ParseOptions`] to use while parsing the file
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{ParseOptions, Zenv};
+    ///
+    /// let parsed = Zenv::new("tests/.env.heredoc", false)
+    ///     .parse_options(ParseOptions {
+    ///         heredoc: true,
+    ///         ..Default::default()
+    ///     })
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("AFTER"), Some(&"after".to_string()))
+    /// ```
+    pub fn parse_options(mut self, parse_options: ParseOptions) -> Self {
+        self.parse_options = parse_options;
+        self
+    }
+
+    /// Set the [`Encoding`] of the file(s). By default, the files must be valid UTF-8.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Encoding, Zenv};
+    ///
+    /// let parsed = Zenv::new("tests/.env.latin1", false)
+    ///     .encoding(Encoding::Latin1)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed.get("NAME"), Some(&"José".to_string()))
+    /// ```
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Read and parse the file(s) from provided path and returns a hashmap
     ///
     /// Example
     /// ```
@@ -59,8 +426,379 @@ impl Zenv {
     /// assert_eq!(parsed.get("BASIC"), Some(&"basic".to_string()))
     /// ```
     pub fn parse(&self) -> Result<HashMap<String, String>> {
-        let path = &self.path;
+        if self.expand {
+            return Ok(self.expand()?.into_iter().collect());
+        }
+
+        let r = self.read()?;
+
+        Ok(self.lines(&r)?.to_hash_map())
+    }
+
+    /// Same as [Zenv::parse], but also returns the file where the effective definition of each
+    /// variable lives i.e. the last file defining it. Variables set with [Zenv::var] have an
+    /// empty path, as they don't come from any file.
+    ///
+    /// Example
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// let parsed = zenv::Zenv::new("tests/.env.base", true)
+    ///     .file("tests/.env.local")
+    ///     .parse_with_source()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed["PORT"], ("80".to_string(), PathBuf::from("tests/.env.base")));
+    /// assert_eq!(parsed["HOST"], ("localhost".to_string(), PathBuf::from("tests/.env.local")));
+    /// ```
+    pub fn parse_with_source(&self) -> Result<HashMap<String, (String, PathBuf)>> {
+        let r = self.read()?;
+        let mut sources = HashMap::new();
+
+        for (path, r) in self.paths.iter().zip(&r) {
+            let mut lines = self.parse_file(path, r)?;
+
+            lines.transform_keys(self.key_transform);
+
+            for (k, _) in lines.to_vec() {
+                sources.insert(k, path);
+            }
+        }
+
+        let lines = self.lines(&r)?;
+
+        let vars = if self.expand {
+            self.expand_lines(&lines)?
+        } else {
+            lines.to_vec()
+        };
+
+        let parsed = vars
+            .into_iter()
+            .map(|(k, v)| {
+                let path = sources
+                    .get(&k)
+                    .map_or_else(PathBuf::new, |x| x.to_path_buf());
+
+                (k, (v, path))
+            })
+            .collect();
 
+        Ok(parsed)
+    }
+
+    /// Same as [Zenv::parse], but also collects the non-fatal issues of the files into `warnings`,
+    /// instead of printing them. These are the lines dropped as they are not `KEY=value` pairs,
+    /// the keys defined twice in the same file and the keys overriding an earlier file. The `+=`
+    /// lines and the variables set with [Zenv::var] are never reported.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Warning, WarningKind};
+    ///
+    /// let mut warnings: Vec<Warning> = Vec::new();
+    ///
+    /// let parsed = zenv::Zenv::new("tests/.env.base", false)
+    ///     .file("tests/.env.local")
+    ///     .parse_with_warnings(&mut warnings)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(parsed["HOST"], "localhost");
+    /// assert_eq!(warnings[0].kind, WarningKind::Override);
+    /// assert_eq!(warnings[0].line, 1);
+    /// ```
+    pub fn parse_with_warnings(
+        &self,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<HashMap<String, String>> {
+        let r = self.read()?;
+        let mut defined: HashMap<String, (&Path, usize)> = HashMap::new();
+
+        for (path, r) in self.paths.iter().zip(&r) {
+            let mut dropped = Vec::new();
+            let mut lines = match self.pairs.get(path) {
+                Some(pairs) => Lines::from_pairs(pairs.iter().cloned()),
+                None => {
+                    Lines::parse_dropped(r, &self.parse_options, &mut dropped).map_err(|e| {
+                        Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+                    })?
+                }
+            };
+
+            lines.transform_keys(self.key_transform);
+
+            let warn = |kind, line, msg| Warning {
+                kind,
+                path: path.to_path_buf(),
+                line,
+                msg,
+            };
+
+            for (line, content) in dropped {
+                warnings.push(warn(
+                    WarningKind::Dropped,
+                    line,
+                    format!("Expected `KEY=value`, the line is dropped - `{}`", content),
+                ));
+            }
+
+            let mut seen: HashMap<&str, usize> = HashMap::new();
+
+            for (line, key, _) in lines.positions().filter(|(_, _, append)| !append) {
+                if let Some(first) = seen.insert(key, line) {
+                    warnings.push(warn(
+                        WarningKind::Duplicate,
+                        line,
+                        format!("Duplicate key, already defined on line {}", first),
+                    ));
+                } else if let Some((prev, at)) = defined.get(key) {
+                    warnings.push(warn(
+                        WarningKind::Override,
+                        line,
+                        format!("`{}` overrides the value of {}:{}", key, prev.display(), at),
+                    ));
+                }
+            }
+
+            for (key, line) in seen {
+                defined.insert(key.to_string(), (path, line));
+            }
+        }
+
+        let lines = self.lines(&r)?;
+
+        if self.expand {
+            return Ok(self.expand_lines(&lines)?.into_iter().collect());
+        }
+
+        Ok(lines.to_hash_map())
+    }
+
+    /// Returns the variables which are referenced by the substitution but are not defined in any
+    /// of the files, or in the running process if [Zenv::env_fallback] is enabled. The result is
+    /// a vec of `(path, line, name)` in the order of the files, see [Lines::missing]. This
+    /// doesn't depend on the `expand` argument of [Zenv::new].
+    ///
+    /// Example
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// let missing = zenv::Zenv::new("tests/.env.expanded", false)
+    ///     .env_fallback(false)
+    ///     .missing()
+    ///     .unwrap();
+    ///
+    /// let path = PathBuf::from("tests/.env.expanded");
+    ///
+    /// assert_eq!(missing[0], (path.clone(), 15, "PATH".to_string()));
+    /// assert_eq!(missing[1], (path, 16, "DONT_EXIST".to_string()));
+    /// ```
+    pub fn missing(&self) -> Result<Vec<(PathBuf, usize, String)>> {
+        let r = self.read()?;
+        let mut vars = self.interpolation()?;
+        vars.extend(self.lines(&r)?.to_hash_map());
+        let opts = self.expand_options();
+        let mut missing = Vec::new();
+
+        for (path, r) in self.paths.iter().zip(&r) {
+            let lines = self.parse_file(path, r)?;
+
+            for (line, name) in lines.missing_in(vars.clone(), &opts) {
+                missing.push((path.clone(), line, name));
+            }
+        }
+
+        Ok(missing)
+    }
+
+    /// Read and parse the file from provided path, then does the variable substitution and
+    /// returns the `(key, value)` pairs in the same order as the file. This doesn't depend on
+    /// the `expand` argument of [Zenv::new].
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.expanded", false).expand().unwrap();
+    ///
+    /// assert_eq!(parsed[0], ("BASIC".to_string(), "basic".to_string()));
+    /// assert_eq!(parsed[1], ("EXPANDED".to_string(), "basic-expanded".to_string()));
+    /// ```
+    pub fn expand(&self) -> Result<Pairs> {
+        let r = self.read()?;
+
+        self.expand_lines(&self.lines(&r)?)
+    }
+
+    /// Read and parse the file from provided path, and returns the `(key, value)` pairs in the
+    /// same order as the file without any variable substitution. This doesn't depend on the
+    /// `expand` argument of [Zenv::new].
+    ///
+    /// Example
+    /// ```
+    /// let parsed = zenv::Zenv::new("tests/.env.expanded", true).raw().unwrap();
+    ///
+    /// assert_eq!(parsed[1], ("EXPANDED".to_string(), "$BASIC-expanded".to_string()));
+    /// ```
+    pub fn raw(&self) -> Result<Pairs> {
+        let r = self.read()?;
+
+        Ok(self.lines(&r)?.to_vec())
+    }
+
+    /// Substitutes the variables inside any text, like a template of a config file, which is not
+    /// parsed as a .env file. The variables of the file(s) are used, which are substituted
+    /// themselves only if `expand` is enabled, and then the running process, see
+    /// [Zenv::env_fallback]. Variables which are not found are substituted with an empty string.
+    ///
+    /// Example
+    /// ```
+    /// let zenv = zenv::Zenv::new("tests/.env.base", true)
+    ///     .file("tests/.env.local")
+    ///     .env_fallback(false);
+    ///
+    /// let out = zenv.render("url = ${URL}\nport = $PORT\nmissing = ${MISSING}").unwrap();
+    ///
+    /// assert_eq!(out, "url = http://localhost:80\nport = 80\nmissing = ");
+    /// ```
+    pub fn render(&self, template: &str) -> Result<String> {
+        let vars = if self.expand {
+            self.expand()?
+        } else {
+            self.raw()?
+        };
+
+        let opts = self.expand_options();
+
+        let mut all = self.interpolation()?;
+        all.extend(vars);
+
+        Ok(Expander::new(all, &opts).expand(template))
+    }
+
+    /// Same as calling [Zenv::raw] and [Zenv::expand], but the file is only read and parsed once.
+    /// Useful to compare the values before and after the substitution.
+    ///
+    /// Example
+    /// ```
+    /// let (raw, expanded) = zenv::Zenv::new("tests/.env.expanded", false)
+    ///     .raw_and_expanded()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(raw[1].1, "$BASIC-expanded");
+    /// assert_eq!(expanded[1].1, "basic-expanded");
+    /// ```
+    pub fn raw_and_expanded(&self) -> Result<(Pairs, Pairs)> {
+        let r = self.read()?;
+        let lines = self.lines(&r)?;
+
+        Ok((lines.to_vec(), self.expand_lines(&lines)?))
+    }
+
+    /// Read and parse the file(s) and returns the value of a single variable, if it is defined.
+    /// The value is substituted only if `expand` is enabled.
+    ///
+    /// Example
+    /// ```
+    /// let zenv = zenv::Zenv::new("tests/.env.expanded", true);
+    ///
+    /// assert_eq!(zenv.get("EXPANDED").unwrap(), Some("basic-expanded".to_string()));
+    /// assert_eq!(zenv.get("MISSING").unwrap(), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let r = self.read()?;
+        let lines = self.lines(&r)?;
+
+        if !self.expand {
+            return Ok(lines.get(key));
+        }
+
+        let val = self
+            .expand_lines(&lines)?
+            .into_iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v);
+
+        Ok(val)
+    }
+
+    fn expand_options(&self) -> ExpandOptions {
+        ExpandOptions {
+            env_fallback: self.env_fallback,
+            compat: self.compat,
+            sigil: self.sigil,
+        }
+    }
+
+    // Substitutes the variables, where the variables of `interpolate_from` are also available
+    fn expand_lines(&self, lines: &Lines) -> Result<Pairs> {
+        Ok(lines.expand_to_vec_in(self.interpolation()?, &self.expand_options()))
+    }
+
+    // Variables of the `interpolate_from` files
+    fn interpolation(&self) -> Result<HashMap<String, String>> {
+        let r = self.read_all(&self.interpolate)?;
+        let mut lines = Lines::empty();
+
+        for (path, r) in self.interpolate.iter().zip(&r) {
+            lines.merge(self.parse_file(path, r)?);
+        }
+
+        lines.transform_keys(self.key_transform);
+
+        Ok(lines.to_hash_map())
+    }
+
+    fn read(&self) -> Result<Vec<String>> {
+        self.read_all(&self.paths)
+    }
+
+    fn read_all(&self, paths: &[PathBuf]) -> Result<Vec<String>> {
+        paths
+            .iter()
+            .map(|path| {
+                if let Some(text) = self.texts.get(path) {
+                    return Ok(text.clone());
+                }
+
+                // Nothing to read, see `parse_file`
+                if self.pairs.contains_key(path) {
+                    return Ok(String::new());
+                }
+
+                Self::check(path)?;
+
+                self.encoding
+                    .decode(read(path)?)
+                    .map_err(|e| Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+            })
+            .collect()
+    }
+
+    fn parse_file<'r>(&self, path: &Path, r: &'r str) -> Result<Lines<'r>> {
+        if let Some(pairs) = self.pairs.get(path) {
+            return Ok(Lines::from_pairs(pairs.iter().cloned()));
+        }
+
+        Lines::parse(r, &self.parse_options)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+    }
+
+    fn lines<'r>(&self, files: &'r [String]) -> Result<Lines<'r>> {
+        let mut lines = Lines::empty();
+
+        for (path, r) in self.paths.iter().zip(files) {
+            lines.merge(self.parse_file(path, r)?);
+        }
+
+        // Treated as the last file, and the values are taken literally
+        lines.merge(Lines::from_pairs(self.vars.iter().cloned()));
+
+        lines.transform_keys(self.key_transform);
+
+        Ok(lines)
+    }
+
+    fn check(path: &Path) -> Result<()> {
         if !path.exists() {
             return Err(Error::new(
                 ErrorKind::NotFound,
@@ -68,15 +806,7 @@ impl Zenv {
             ));
         }
 
-        let r = read_to_string(path)?;
-        let lines = Lines::from(r.as_str());
-
-        let hash = match self.expand {
-            true => lines.expand(),
-            false => lines.to_hash_map(),
-        };
-
-        Ok(hash)
+        Ok(())
     }
 
     /// Parse the file using [Zenv::parse] and sets the environment variable
@@ -98,6 +828,79 @@ impl Zenv {
     }
 }
 
+/// Check that the required variables are present in the parsed variables
+#[cfg(feature = "std")]
+pub trait Require {
+    /// Returns the keys which are either missing or empty, in the same order as `keys`
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Require, Zenv};
+    ///
+    /// let parsed = Zenv::new("tests/.env.basic", false).parse().unwrap();
+    ///
+    /// assert_eq!(parsed.require(&["BASIC", "DOUBLE_QUOTES"]), Ok(()));
+    /// assert_eq!(
+    ///     parsed.require(&["BASIC", "EMPTY", "MISSING"]),
+    ///     Err(vec!["EMPTY".to_string(), "MISSING".to_string()])
+    /// );
+    /// ```
+    fn require(&self, keys: &[&str]) -> std::result::Result<(), Vec<String>>;
+}
+
+#[cfg(feature = "std")]
+impl Require for HashMap<String, String> {
+    fn require(&self, keys: &[&str]) -> std::result::Result<(), Vec<String>> {
+        let missing: Vec<String> = keys
+            .iter()
+            .filter(|k| self.get(**k).map_or(true, |v| v.is_empty()))
+            .map(|k| k.to_string())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+}
+
+/// Create a new instance of Zenv, without variable substitution, only if the file exists and
+/// can be opened for reading. Use [`Zenv::substitution`] to enable the substitution.
+///
+/// Example
+/// ```
+/// use std::{convert::TryFrom, path::Path};
+///
+/// let zenv = zenv::Zenv::try_from(Path::new("tests/.env.basic")).unwrap();
+///
+/// assert_eq!(zenv.parse().unwrap().get("BASIC"), Some(&"basic".to_string()));
+///
+/// assert!(zenv::Zenv::try_from(Path::new("tests/.env.missing")).is_err());
+/// ```
+#[cfg(feature = "std")]
+impl TryFrom<&Path> for Zenv {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Self> {
+        Self::try_from(path.to_path_buf())
+    }
+}
+
+/// Same as `TryFrom<&Path>` but takes the ownership of the path
+#[cfg(feature = "std")]
+impl TryFrom<PathBuf> for Zenv {
+    type Error = Error;
+
+    fn try_from(path: PathBuf) -> Result<Self> {
+        Self::check(&path)?;
+
+        File::open(&path)?;
+
+        Ok(Self::with_path(path, false))
+    }
+}
+
 /// This macro can be used as a shortcut for [`Zenv`]
 ///
 /// Example
@@ -112,6 +915,7 @@ impl Zenv {
 /// // or with variable substitution
 /// zenv!(".env.development", true);
 /// ````
+#[cfg(feature = "std")]
 #[macro_export]
 macro_rules! zenv {
     () => {
@@ -1,15 +1,96 @@
 mod cli;
+mod color;
+mod crypto;
+mod edit;
+mod envfile;
+mod format;
+mod glob;
+mod http;
 mod info;
+mod parallel;
+mod rlimit;
+mod sha256;
+mod signal;
+mod toml;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::Path;
 use std::process::exit;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, ExitStatus};
+use std::thread;
 
-use cli::Cli;
-use zenv::Zenv;
+use cli::{Cli, OnSpawnError, Subcommand};
+use color::Color;
+use envfile::EnvFile;
+use format::Formatter;
+use zenv::{CommandOptions, ExpandOptions, Lines, ParseOptions, Require, Zenv};
 
 use crate::info::{NAME, VERSION};
 
-fn bootstrap() -> Result<i32, String> {
-    let args = Cli::parse()?;
+// Exit codes of zenv itself, following the shell conventions. Any other failure exits with 1.
+const USAGE: i32 = 2;
+const INVALID_FILE: i32 = 3;
+const NOT_EXECUTABLE: i32 = 126;
+const NOT_FOUND: i32 = 127;
+
+// Separator of the PATH-like variables
+const PATH_SEP: char = if cfg!(windows) { ';' } else { ':' };
+
+// Where the variables of a file come from
+enum Source {
+    // Read while parsing
+    File,
+
+    // Already in memory i.e. downloaded
+    Text(String),
+
+    // Already parsed i.e. the [env] table of a TOML file
+    Pairs(Vec<(String, String)>),
+}
+
+// Error which decides the exit code
+struct Failure {
+    code: i32,
+    msg: String,
+}
+
+impl Failure {
+    // Invalid or missing arguments
+    fn usage(msg: impl Into<String>) -> Self {
+        Self {
+            code: USAGE,
+            msg: msg.into(),
+        }
+    }
+
+    // A file which can't be read or parsed
+    fn file(msg: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_FILE,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(msg: String) -> Self {
+        Self { code: 1, msg }
+    }
+}
+
+impl From<&str> for Failure {
+    fn from(msg: &str) -> Self {
+        Self::from(msg.to_string())
+    }
+}
+
+fn bootstrap(color: &mut Color) -> Result<i32, Failure> {
+    let args = Cli::parse().map_err(Failure::usage)?;
+
+    *color = args.color;
 
     if args.version {
         println!("{} {}", NAME, VERSION);
@@ -17,42 +98,735 @@ fn bootstrap() -> Result<i32, String> {
     }
 
     if args.help {
-        print!("{}", Cli::help_doc());
+        print!("{}", Cli::help_doc(args.color.enabled(&io::stdout())));
+        return Ok(0);
+    }
+
+    match &args.subcommand {
+        Some(Subcommand::Set(pair)) => {
+            edit::set(
+                Path::new(args.file().map_err(Failure::usage)?),
+                pair,
+                &args.parse_options(),
+            )?;
+
+            return Ok(0);
+        }
+        Some(Subcommand::CommentOut(key)) | Some(Subcommand::Uncomment(key)) => {
+            let comment = matches!(args.subcommand, Some(Subcommand::CommentOut(_)));
+            let path = args.file().map_err(Failure::usage)?;
+
+            if edit::toggle(Path::new(path), key, comment, &args.parse_options())? == 0 {
+                eprintln!(
+                    "{}: Nothing to change, `{}` is not found in {}",
+                    NAME, key, path
+                );
+            }
+
+            return Ok(0);
+        }
+        Some(Subcommand::Encrypt) => {
+            let path = args.file().map_err(Failure::usage)?;
+            let key = args
+                .key()
+                .map_err(Failure::usage)?
+                .ok_or_else(|| Failure::usage("`encrypt` requires --decrypt-key or ZENV_KEY"))?;
+
+            let bytes = fs::read(path).map_err(|e| Failure::file(format!("{}: {}", path, e)))?;
+
+            if crypto::is_encrypted(&bytes) {
+                return Err(format!("{}: The file is already encrypted", path).into());
+            }
+
+            let out = crypto::encrypt(&bytes, &key)?;
+
+            match &args.out {
+                Some(path) => fs::write(path, out)
+                    .map_err(|e| format!("Unable to write the output - {}", e))?,
+                None => print!("{}", out),
+            }
+
+            return Ok(0);
+        }
+        Some(Subcommand::Lint) => {
+            if args.paths.is_empty() {
+                return Err(Failure::usage("-f/--file option is required"));
+            }
+
+            let mut errors = 0;
+
+            for path in &args.paths {
+                let src = fs::read(path)
+                    .and_then(|x| args.encoding.decode(x))
+                    .map_err(|e| Failure::file(format!("{}: {}", path, e)))?;
+
+                let opts = ParseOptions {
+                    strict: true,
+                    ..args.parse_options()
+                };
+
+                for e in Lines::errors(&src, &opts) {
+                    errors += 1;
+                    println!("{}:{}: error: {} - `{}`", path, e.line, e.msg, e.content);
+                }
+
+                for x in zenv::lint(&src, &opts) {
+                    println!("{}:{}: warning: {} - `{}`", path, x.line, x.msg, x.content);
+                }
+            }
+
+            return Ok(if errors > 0 { 1 } else { 0 });
+        }
+        _ => {}
+    }
+
+    let format = args.format().map_err(Failure::usage)?;
+
+    if !args.sha256.is_empty() && args.sha256.len() != args.paths.len() {
+        return Err(Failure::usage(
+            "--expect-sha256 must be given once for each -f/--file",
+        ));
+    }
+
+    let key = args.key().map_err(Failure::usage)?;
+
+    let mut sources: Vec<(&str, Source)> = Vec::with_capacity(args.paths.len());
+
+    for (i, path) in args.paths.iter().enumerate() {
+        let expected = args.sha256.get(i);
+
+        // Otherwise the file is read while parsing
+        if expected.is_none() && key.is_none() {
+            sources.push((path, Source::File));
+            continue;
+        }
+
+        // Read only once, so the checked content is also the parsed one
+        let mut bytes = fs::read(path).map_err(|e| Failure::file(format!("{}: {}", path, e)))?;
+
+        // Of the file as it is on the disk, even if it is encrypted
+        if let Some(expected) = expected {
+            let actual = sha256::hex(&bytes);
+
+            if &actual != expected {
+                return Err(Failure::file(format!(
+                    "{}: SHA-256 mismatch, expected {} but found {}",
+                    path, expected, actual
+                )));
+            }
+        }
+
+        if let Some(key) = &key {
+            if crypto::is_encrypted(&bytes) {
+                bytes = crypto::decrypt(&bytes, key)
+                    .map_err(|e| Failure::file(format!("{}: {}", path, e)))?;
+            }
+        }
+
+        let src = args
+            .encoding
+            .decode(bytes)
+            .map_err(|e| Failure::file(format!("{}: {}", path, e)))?;
+
+        sources.push((path, Source::Text(src)));
+    }
+
+    // The downloaded files are loaded after the local ones
+
+    for url in &args.urls {
+        sources.push((url, Source::Text(http::get(url)?)));
+    }
+
+    for path in &args.tomls {
+        sources.push((
+            path,
+            Source::Pairs(toml::read(path).map_err(Failure::file)?),
+        ));
+    }
+
+    let names: Vec<&str> = sources.iter().map(|x| x.0).collect();
+
+    let ((first, src), rest) = sources
+        .split_first()
+        .ok_or_else(|| Failure::usage("-f/--file, --url or --toml option is required"))?;
+
+    let zenv = match src {
+        Source::File => Zenv::new(first, args.expand),
+        Source::Text(src) => Zenv::from_text(first, src.as_str(), args.expand),
+        Source::Pairs(pairs) => Zenv::from_pairs(first, pairs.clone(), args.expand),
+    };
+
+    let zenv = rest.iter().fold(zenv, |z, (name, src)| match src {
+        Source::File => z.file(name),
+        Source::Text(src) => z.text(name, src.as_str()),
+        Source::Pairs(pairs) => z.pairs(name, pairs.clone()),
+    });
+
+    let zenv = args
+        .interpolate_from
+        .iter()
+        .fold(zenv, |z, path| z.interpolate_from(path));
+
+    let zenv = args
+        .vars
+        .iter()
+        .fold(zenv, |z, (k, v)| z.var(k, v))
+        .env_fallback(!args.no_env_fallback)
+        .compat(args.compat)
+        .sigil(args.sigil)
+        .encoding(args.encoding)
+        .key_transform(args.key_transform)
+        .parse_options(args.parse_options());
+
+    if let Some(template) = &args.template {
+        let src = fs::read_to_string(template)
+            .map_err(|e| Failure::file(format!("Unable to read the template - {}", e)))?;
+
+        let out = zenv
+            .render(&src)
+            .map_err(|e| Failure::file(e.to_string()))?;
+
+        match &args.out {
+            Some(path) => {
+                fs::write(path, out).map_err(|e| format!("Unable to write the output - {}", e))?
+            }
+            None => print!("{}", out),
+        }
+
+        return Ok(0);
+    }
+
+    if let Some(Subcommand::Get(key)) = &args.subcommand {
+        let val = zenv
+            .get(key)
+            .map_err(|e| Failure::file(e.to_string()))?
+            .ok_or_else(|| format!("Variable is not defined - {}", key))?;
+
+        println!("{}", val);
+
+        return Ok(0);
+    }
+
+    if args.print_missing {
+        for (path, line, name) in zenv.missing().map_err(|e| Failure::file(e.to_string()))? {
+            println!("{}:{}: {}", path.display(), line, name);
+        }
+
+        return Ok(0);
+    }
+
+    if args.verbose {
+        let mut warnings = Vec::new();
+
+        zenv.parse_with_warnings(&mut warnings)
+            .map_err(|e| Failure::file(e.to_string()))?;
+
+        for w in warnings {
+            eprintln!("{}: {}", NAME, w);
+        }
+    }
+
+    // Keeping the order of the file, for printing
+    let (ordered, expanded) = if args.expand {
+        let (raw, expanded) = zenv
+            .raw_and_expanded()
+            .map_err(|e| Failure::file(e.to_string()))?;
+
+        // Number of the values which are changed by the substitution
+        let count = raw
+            .iter()
+            .zip(&expanded)
+            .filter(|(r, e)| r.1 != e.1)
+            .count();
+
+        (expanded, Some(count))
+    } else {
+        (zenv.raw().map_err(|e| Failure::file(e.to_string()))?, None)
+    };
+
+    if let Some(Subcommand::Compare) = &args.subcommand {
+        let vars: HashMap<_, _> = ordered.iter().cloned().collect();
+        let mut seen = HashSet::with_capacity(vars.len());
+        let mut drift = false;
+
+        // Only the status is printed, as the values can be secrets
+        for (key, _) in &ordered {
+            if !seen.insert(key) {
+                continue;
+            }
+
+            let status = match env::var_os(key) {
+                Some(x) if x == OsStr::new(&vars[key]) => "same",
+                Some(_) => "differs",
+                None => "unset",
+            };
+
+            drift |= status != "same";
+
+            println!("{}: {}", key, status);
+        }
+
+        return Ok(if args.strict && drift { 1 } else { 0 });
+    }
+
+    if args.require_nonempty && ordered.is_empty() {
+        return Err(format!("No variables found in {}", names.join(", ")).into());
+    }
+
+    let required: Vec<&str> = args.require.iter().map(String::as_str).collect();
+
+    ordered
+        .iter()
+        .cloned()
+        .collect::<HashMap<_, _>>()
+        .require(&required)
+        .map_err(|missing| format!("Missing required variables - {}", missing.join(", ")))?;
+
+    // Removed from the environment of the command, including the inherited variables
+    let unset: Vec<String> = env::vars_os()
+        .filter_map(|(k, _)| k.into_string().ok())
+        .chain(ordered.iter().map(|(k, _)| k.clone()))
+        .filter(|k| args.unset.iter().any(|x| glob::matches(x, k)))
+        .collect();
+
+    // Not an error, as the variables can be optional
+    if args.verbose {
+        let only = args
+            .only
+            .iter()
+            .filter(|x| !ordered.iter().any(|(k, _)| glob::matches(x, k)));
+
+        let unset = args
+            .unset
+            .iter()
+            .filter(|x| !unset.iter().any(|k| glob::matches(x, k)));
+
+        for pattern in only.chain(unset) {
+            eprintln!("{}: `{}` doesn't match any variable", NAME, pattern);
+        }
+    }
+
+    let mut ordered: Vec<_> = ordered
+        .into_iter()
+        .filter(|(k, _)| args.only.is_empty() || args.only.iter().any(|x| glob::matches(x, k)))
+        .filter(|(k, _)| !unset.contains(k))
+        .map(|(k, v)| match &args.add_prefix {
+            Some(prefix) => (format!("{}{}", prefix, k), v),
+            None => (k, v),
+        })
+        .collect();
+
+    if args.fail_on_override || args.warn_on_override {
+        let last: HashMap<&str, &str> = ordered
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let mut seen = HashSet::with_capacity(last.len());
+
+        // Only the variables which the command would inherit otherwise
+        let overrides: Vec<&str> = ordered
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .filter(|k| seen.insert(*k))
+            .filter(|k| args.inherit.is_empty() || args.inherit.iter().any(|x| x == k))
+            .filter(|k| env::var_os(k).is_some_and(|x| x != OsStr::new(last[k])))
+            .collect();
+
+        if args.fail_on_override && !overrides.is_empty() {
+            return Err(format!(
+                "Variables override the inherited values - {}",
+                overrides.join(", ")
+            )
+            .into());
+        }
+
+        for key in overrides {
+            eprintln!("{}: `{}` overrides the inherited value", NAME, key);
+        }
+    }
+
+    let paths = args
+        .append_path
+        .iter()
+        .map(|x| (x, true))
+        .chain(args.prepend_path.iter().map(|x| (x, false)));
+
+    for ((key, dir), append) in paths {
+        // The last definition is changed in place, to keep the order
+        let prev = ordered.iter_mut().rev().find(|(k, _)| k == key);
+
+        // Otherwise the inherited value, if the command gets it
+        let inherited =
+            !unset.contains(key) && (args.inherit.is_empty() || args.inherit.contains(key));
+
+        let val = match &prev {
+            Some((_, v)) => v.clone(),
+            None if inherited => env::var(key).unwrap_or_default(),
+            None => String::new(),
+        };
+
+        let val = match val.as_str() {
+            "" => dir.clone(),
+            _ if append => format!("{}{}{}", val, PATH_SEP, dir),
+            _ => format!("{}{}{}", dir, PATH_SEP, val),
+        };
+
+        match prev {
+            Some((_, v)) => *v = val,
+            None => ordered.push((key.clone(), val)),
+        }
+    }
+
+    let vars: HashMap<String, String> = ordered.iter().cloned().collect();
+
+    // Failing early, instead of an unclear error from the spawn i.e. E2BIG
+    if let Some(max) = args.max_var_size {
+        if let Some((k, v)) = ordered.iter().find(|(_, v)| v.len() > max) {
+            return Err(format!(
+                "Value of `{}` is {} bytes, more than --max-var-size {}",
+                k,
+                v.len(),
+                max
+            )
+            .into());
+        }
+    }
+
+    if let Some(max) = args.max_env_size {
+        let size: usize = vars.iter().map(|(k, v)| k.len() + v.len() + 1).sum();
+
+        if size > max {
+            return Err(format!(
+                "Variables are {} bytes, more than --max-env-size {}",
+                size, max
+            )
+            .into());
+        }
+    }
+
+    if let Some(format) = format {
+        // Only the last definition of the key is printed
+        let mut seen = HashSet::with_capacity(ordered.len());
+        let mut vars: Vec<_> = ordered
+            .into_iter()
+            .rev()
+            .filter(|(k, _)| seen.insert(k.clone()))
+            .collect();
+        vars.reverse();
+
+        // Same as the command, the file takes precedence over the inherited variables
+        if args.print_effective {
+            let mut effective: Vec<_> = inherited(&args.inherit)
+                .into_iter()
+                .filter(|(k, _)| !unset.contains(k))
+                .filter(|(k, _)| !vars.iter().any(|(x, _)| x == k))
+                .collect();
+
+            effective.append(&mut vars);
+            vars = effective;
+        }
+
+        if args.sort {
+            vars.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        if args.print_header && format.has_comments() {
+            let src = match src {
+                Source::Text(src) => src.clone(),
+                _ => fs::read(first)
+                    .and_then(|x| args.encoding.decode(x))
+                    .map_err(|e| Failure::file(format!("{}: {}", first, e)))?,
+            };
+
+            let header = format::header(&src);
+
+            if !header.is_empty() {
+                println!("{}\n", header.join("\n"));
+            }
+        }
+
+        let formatter = Formatter {
+            format,
+            escape_newlines: args.escape_newlines,
+        };
+
+        print!("{}", formatter.render(&vars)?);
         return Ok(0);
     }
 
-    let fpath = args.path()?;
+    let binary = args.binary().map_err(Failure::usage)?;
 
-    let binary = args.binary()?;
+    // Every command is a single argument with `--parallel`, which is split like a shell
+    let mut commands: Vec<(OsString, Vec<OsString>)> = if args.parallel {
+        std::iter::once(binary)
+            .chain(&args.bin_args)
+            .map(|x| {
+                let cmd = x.to_str().ok_or("Commands must be valid UTF-8")?;
+                let mut parts = parallel::split(cmd)?.into_iter().map(OsString::from);
+                let binary = parts.next().ok_or("Command can't be empty")?;
+
+                Ok((binary, parts.collect()))
+            })
+            .collect::<Result<_, String>>()
+            .map_err(Failure::usage)?
+    } else {
+        vec![(binary.clone(), args.bin_args.clone())]
+    };
 
-    let vars = Zenv::new(fpath, args.expand)
-        .parse()
-        .map_err(|e| e.to_string())?;
+    if args.expand_args {
+        let opts = ExpandOptions {
+            env_fallback: !args.no_env_fallback,
+            compat: args.compat,
+            sigil: args.sigil,
+        };
 
-    let mut program = Command::new(&binary)
-        .args(&args.bin_args)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .envs(vars)
-        .spawn()
-        .map_err(|_| format!("Unable to spawn program - `{}`", binary.to_str().unwrap()))?;
+        // Arguments which are not valid UTF-8 are passed as they are
+        let expand = |x: &mut OsString| {
+            if let Some(s) = x.to_str() {
+                *x = OsString::from(zenv::substitute(s, &vars, &opts));
+            }
+        };
+
+        for (binary, bin_args) in &mut commands {
+            expand(binary);
+            bin_args.iter_mut().for_each(expand);
+        }
+    }
 
-    let code = program
-        .wait()
-        .map_err(|e| e.to_string())?
-        .code()
-        .ok_or("Failed to grab the exit code")?;
+    // Removed when dropped i.e. after the commands exit, or on any failure
+    let _env_file = if args.emit_env_file {
+        let mut sorted: Vec<_> = vars.iter().collect();
+        sorted.sort();
+
+        let file = EnvFile::create(&sorted)?;
+        let path = file.path.to_string_lossy().into_owned();
+
+        let replace = |x: &mut OsString| {
+            if let Some(s) = x.to_str().filter(|s| s.contains(envfile::PLACEHOLDER)) {
+                *x = OsString::from(s.replace(envfile::PLACEHOLDER, &path));
+            }
+        };
+
+        for (binary, bin_args) in &mut commands {
+            replace(binary);
+            bin_args.iter_mut().for_each(replace);
+        }
+
+        Some(file)
+    } else {
+        None
+    };
+
+    if args.verbose {
+        let expanded = expanded.map_or(String::new(), |x| format!(" ({} expanded)", x));
+        let commands: Vec<_> = commands
+            .iter()
+            .map(|(binary, bin_args)| {
+                let command: Vec<_> = std::iter::once(binary)
+                    .chain(bin_args)
+                    .map(|x| x.to_string_lossy())
+                    .collect();
+
+                command.join(" ")
+            })
+            .collect();
+
+        eprintln!(
+            "{}: loaded {} vars from {}{}, running: {}",
+            NAME,
+            vars.len(),
+            names.join(", "),
+            expanded,
+            commands.join(", ")
+        );
+    }
+
+    let opts = CommandOptions {
+        inherit: args.inherit.clone(),
+        unset,
+    };
+
+    if !args.limits.is_empty() && !rlimit::SUPPORTED {
+        eprintln!(
+            "{}: --limit-* options are not supported on this platform, ignored",
+            NAME
+        );
+    }
+
+    args.limits.check()?;
+
+    let mut attempt = 0;
+
+    loop {
+        let code = run(&args, &commands, &vars, &opts)?;
+
+        // A clean exit is never retried, and the last exit code is kept. Same if `zenv` is
+        // stopped, as the commands are stopped by the forwarded signal
+        if code == 0 || attempt == args.retry || signal::Forward::received() {
+            return Ok(code);
+        }
+
+        attempt += 1;
+
+        eprintln!(
+            "{}: exited with {}, retrying in {:?} ({}/{})",
+            NAME, code, args.retry_delay, attempt, args.retry
+        );
+
+        thread::sleep(args.retry_delay);
+    }
+}
+
+// Spawns every command and waits for all of them, returning the exit code
+fn run(
+    args: &Cli,
+    commands: &[(OsString, Vec<OsString>)],
+    vars: &HashMap<String, String>,
+    opts: &CommandOptions,
+) -> Result<i32, Failure> {
+    let mut programs = Vec::with_capacity(commands.len());
+
+    for (binary, bin_args) in commands {
+        // On windows, `npm` is actually `npm.cmd` so try the common extensions as well
+        let candidates = if cfg!(windows) {
+            zenv::exe_candidates(binary)
+        } else {
+            vec![binary.clone()]
+        };
+
+        let program = spawn(&candidates, |name| {
+            let mut cmd = zenv::build_command(name, bin_args, vars, opts);
+            args.limits.apply(&mut cmd);
+            cmd
+        });
+
+        match program {
+            Ok(program) => programs.push(program),
+            Err(e)
+                if e.kind() == io::ErrorKind::NotFound
+                    && args.on_spawn_error == OnSpawnError::Ignore =>
+            {
+                eprintln!(
+                    "{}: `{}` is not found, ignored",
+                    NAME,
+                    binary.to_string_lossy()
+                );
+            }
+            Err(e) => {
+                // Not leaving the already started commands behind
+                for mut program in programs {
+                    program.kill().ok();
+                    program.wait().ok();
+                }
+
+                let code = match e.kind() {
+                    io::ErrorKind::NotFound => NOT_FOUND,
+                    _ => NOT_EXECUTABLE,
+                };
+
+                return Err(Failure {
+                    code,
+                    msg: format!("Unable to spawn program - `{}`", binary.to_string_lossy()),
+                });
+            }
+        }
+    }
+
+    // Removed when dropped, after every command exits. Set before the pid file is written, so
+    // that the signals can be sent right after it
+    let forward = signal::Forward::new(&programs);
+
+    if let Some(pid_file) = &args.pid_file {
+        let pids: Vec<_> = programs.iter().map(|x| x.id().to_string()).collect();
+
+        fs::write(pid_file, pids.join("\n"))
+            .map_err(|e| format!("Unable to write the pid file - {}", e))?;
+    }
+
+    let statuses: Vec<_> = programs
+        .iter_mut()
+        .enumerate()
+        .map(|(idx, x)| {
+            let status = x.wait();
+            forward.exited(idx);
+            status
+        })
+        .collect();
+
+    drop(forward);
+
+    if let Some(pid_file) = &args.pid_file {
+        fs::remove_file(pid_file).ok();
+    }
+
+    let mut code = 0;
+
+    // The first failing command decides the exit code
+    for status in statuses {
+        let status =
+            exit_code(&status.map_err(|e| e.to_string())?).ok_or("Failed to grab the exit code")?;
+
+        if code == 0 {
+            code = status;
+        }
+    }
 
     Ok(code)
 }
 
+// A process killed by a signal has no exit code, so following the shell convention i.e. 128 + signal
+#[cfg(unix)]
+fn exit_code(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+
+    status.code().or_else(|| status.signal().map(|x| 128 + x))
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: &ExitStatus) -> Option<i32> {
+    status.code()
+}
+
+// Variables which are passed to the command from the current environment, sorted by the key
+fn inherited(inherit: &[String]) -> Vec<(String, String)> {
+    let mut vars: Vec<_> = env::vars_os()
+        .filter(|(k, _)| inherit.is_empty() || inherit.iter().any(|x| k == x.as_str()))
+        .map(|(k, v)| {
+            (
+                k.to_string_lossy().into_owned(),
+                v.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+
+    vars.sort();
+    vars
+}
+
+// Spawns the first candidate which is found
+fn spawn(candidates: &[OsString], build: impl Fn(&OsStr) -> Command) -> io::Result<Child> {
+    let mut last_err = io::Error::from(io::ErrorKind::NotFound);
+
+    for name in candidates {
+        match build(name).spawn() {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => last_err = e,
+            res => return res,
+        }
+    }
+
+    Err(last_err)
+}
+
 fn main() {
-    match bootstrap() {
+    // Used for the errors, including the ones before the arguments are parsed
+    let mut color = Color::Auto;
+
+    match bootstrap(&mut color) {
         Ok(code) => exit(code),
         Err(e) => {
-            eprintln!("ERROR :: {}", e);
-            exit(1)
+            let prefix = color::error("ERROR ::", color.enabled(&io::stderr()));
+            eprintln!("{} {}", prefix, e.msg);
+            exit(e.code)
         }
     };
 }
@@ -0,0 +1,103 @@
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{Error, ErrorKind, Result},
+    os::unix::io::AsRawFd,
+    path::Path,
+    ptr, slice, str,
+};
+
+/// A read-only memory map of a file, so that a large file can be parsed with [`Lines::parse`]
+/// without reading it into a `String` first. The keys of the parsed [`Lines`] are borrowed from
+/// the map, while the values are still owned, as the quotes and the escapes are removed from
+/// them. Only available on Unix, with the `mmap` feature.
+///
+/// Example
+/// ```
+/// use zenv::{Lines, Mmap, ParseOptions};
+///
+/// // SAFETY: The file is not modified while it is mapped
+/// let map = unsafe { Mmap::open("tests/.env.basic") }.unwrap();
+/// let lines = Lines::parse(map.as_str().unwrap(), &ParseOptions::default()).unwrap();
+///
+/// assert_eq!(lines.get("BASIC"), Some("basic".to_string()));
+/// ```
+///
+/// [`Lines`]: crate::Lines
+/// [`Lines::parse`]: crate::Lines::parse
+#[derive(Debug)]
+pub struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// The map is never written to, so it can be shared the same as a `&[u8]`
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    /// Maps the whole file at `path`, which is unmapped when dropped
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped, by this or any other
+    /// process, as the content is read directly from the file. Otherwise, the borrowed `&str`
+    /// can change or even become invalid UTF-8.
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "The file is too large to map"))?;
+
+        // An empty map is an error, while an empty file is not
+        if len == 0 {
+            return Ok(Self {
+                ptr: ptr::null_mut(),
+                len,
+            });
+        }
+
+        let ptr = libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        );
+
+        if ptr == libc::MAP_FAILED {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(Self { ptr, len })
+    }
+
+    /// Returns the content of the file as it is
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        // SAFETY: The map is valid and readable for `len` bytes, till it is dropped
+        unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+
+    /// Returns the content of the file, which must be valid UTF-8
+    pub fn as_str(&self) -> Result<&str> {
+        str::from_utf8(self.as_bytes()).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The file is not valid UTF-8 - {}", e),
+            )
+        })
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: Mapped by `open` with the same length, and not used after this
+            unsafe { libc::munmap(self.ptr, self.len) };
+        }
+    }
+}
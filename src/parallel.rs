@@ -0,0 +1,56 @@
+const S_QUOTE: char = '\'';
+const D_QUOTE: char = '"';
+const B_SLASH: char = '\\';
+
+// Splits the command into the arguments, like a shell but without any substitution. Spaces can be
+// kept by quoting i.e. `'a b'` or `"a b"`, or by escaping i.e. `a\ b`.
+pub fn split(cmd: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut chars = cmd.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            S_QUOTE | D_QUOTE => {
+                let arg = arg.get_or_insert_with(String::new);
+                let mut closed = false;
+
+                while let Some(x) = chars.next() {
+                    match x {
+                        x if x == c => {
+                            closed = true;
+                            break;
+                        }
+                        // Only `"` and `\` can be escaped inside the double quotes
+                        B_SLASH if c == D_QUOTE => match chars.next() {
+                            Some(n @ (D_QUOTE | B_SLASH)) => arg.push(n),
+                            Some(n) => {
+                                arg.push(x);
+                                arg.push(n);
+                            }
+                            None => arg.push(x),
+                        },
+                        x => arg.push(x),
+                    }
+                }
+
+                if !closed {
+                    return Err(format!("Unclosed quote in the command - `{}`", cmd));
+                }
+            }
+            B_SLASH => {
+                let arg = arg.get_or_insert_with(String::new);
+
+                if let Some(n) = chars.next() {
+                    arg.push(n);
+                }
+            }
+            c if c.is_whitespace() => args.extend(arg.take()),
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    args.extend(arg);
+
+    Ok(args)
+}
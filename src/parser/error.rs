@@ -0,0 +1,34 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// Error returned by [`Lines::parse`](super::Lines::parse) when a line can't be parsed
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Line number, starting from 1
+    pub line: usize,
+
+    /// Content of the line
+    pub content: String,
+
+    /// What went wrong
+    pub msg: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, content: &str, msg: impl Into<String>) -> Self {
+        Self {
+            line,
+            content: content.to_string(),
+            msg: msg.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {} - `{}`", self.line, self.msg, self.content)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
@@ -0,0 +1,340 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    env,
+    iter::Peekable,
+    str::Chars,
+};
+
+use super::options::{Compat, ExpandOptions};
+
+const DOLLAR: char = '$';
+const L_BRACE: char = '{';
+const R_BRACE: char = '}';
+const BANG: char = '!';
+const SLASH: char = '/';
+const B_SLASH: char = '\\';
+const DASH: char = '-';
+const COLON: char = ':';
+
+/// Substitutes the variables i.e. `${VAR}` and `$VAR` inside any text, like a template or an
+/// argument, with the same rules as the double quoted values. See [`ExpandOptions::sigil`] for the
+/// other styles i.e. `%VAR%`. The variables are looked up in
+/// `vars` and then in the running process, if [`ExpandOptions::env_fallback`] is enabled.
+/// Variables which are not found are substituted with an empty string.
+///
+/// Example
+/// ```
+/// use std::collections::HashMap;
+/// use zenv::{substitute, ExpandOptions};
+///
+/// let mut vars = HashMap::new();
+/// vars.insert("RUNNER".to_string(), "node".to_string());
+///
+/// let opts = ExpandOptions {
+///     env_fallback: false,
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(substitute("${RUNNER} app.js $MISSING", &vars, &opts), "node app.js ");
+/// ```
+pub fn substitute(text: &str, vars: &HashMap<String, String>, opts: &ExpandOptions) -> String {
+    Expander::new(vars.clone(), opts).expand(text)
+}
+
+/// Does the variable substitution inside a value
+pub(crate) struct Expander<'o> {
+    vars: HashMap<String, String>,
+    opts: &'o ExpandOptions,
+
+    // Variables of the running process which are already looked up, as they can't change
+    env: RefCell<HashMap<String, Option<String>>>,
+
+    // Variables which are not found, since the last `take_missing`
+    missing: RefCell<Vec<String>>,
+
+    // Variables which are (re)defined after `new`, and the ones which are looked up before that
+    defined: HashSet<String>,
+    forward: RefCell<HashSet<String>>,
+}
+
+impl<'o> Expander<'o> {
+    pub fn new(vars: HashMap<String, String>, opts: &'o ExpandOptions) -> Self {
+        Self {
+            vars,
+            opts,
+            env: RefCell::default(),
+            missing: RefCell::default(),
+            defined: HashSet::new(),
+            forward: RefCell::default(),
+        }
+    }
+
+    /// (Re)Define the variable which will be used by the following substitutions
+    pub fn define(&mut self, key: &str, val: String) {
+        self.defined.insert(key.to_string());
+        self.vars.insert(key.to_string(), val);
+    }
+
+    /// Removes the variable, so that it is looked up in the running process
+    pub fn undefine(&mut self, key: &str) {
+        self.vars.remove(key);
+    }
+
+    fn is_ident(&self, c: &char) -> bool {
+        match self.opts.compat {
+            Compat::Bash => c.is_ascii_alphanumeric() || c == &'_',
+            Compat::Zenv => c.is_alphanumeric() || c == &'_',
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<String> {
+        match self.vars.get(key) {
+            Some(x) => {
+                if !self.defined.contains(key) {
+                    self.forward.borrow_mut().insert(key.to_string());
+                }
+
+                Some(x.to_string())
+            }
+            None if self.opts.env_fallback => self
+                .env
+                .borrow_mut()
+                .entry(key.to_string())
+                .or_insert_with(|| env::var(key).ok())
+                .clone(),
+            None => None,
+        }
+    }
+
+    // Same as `lookup`, but an undefined variable is recorded as missing and is empty
+    fn lookup_or_empty(&self, key: &str) -> String {
+        self.lookup(key).unwrap_or_else(|| {
+            let mut missing = self.missing.borrow_mut();
+
+            if !key.is_empty() && !missing.iter().any(|x| x == key) {
+                missing.push(key.to_string());
+            }
+
+            String::new()
+        })
+    }
+
+    /// Returns and clears the variables which are looked up with their value from `new`, i.e.
+    /// before any `define`
+    pub fn take_forward(&self) -> HashSet<String> {
+        self.forward.take()
+    }
+
+    /// Returns and clears the variables which were not found by the previous substitutions
+    pub fn take_missing(&self) -> Vec<String> {
+        self.missing.take()
+    }
+
+    pub fn expand(&self, val: &str) -> String {
+        self.expand_inner(val, false)
+    }
+
+    // Inside `${...}`, where `\}` is a literal `}` when `unescape` is enabled
+    fn expand_inner(&self, val: &str, unescape: bool) -> String {
+        if self.opts.sigil != DOLLAR {
+            return self.expand_enclosed(val, self.opts.sigil);
+        }
+
+        let mut new_val = String::with_capacity(val.len());
+        let mut chars = val.chars().peekable();
+
+        let bash = self.opts.compat == Compat::Bash;
+
+        while let Some(c) = chars.next() {
+            // Escaped i.e. `\$` is a literal `$`
+            if bash && c == B_SLASH && chars.peek() == Some(&DOLLAR) {
+                chars.next();
+                new_val.push(DOLLAR);
+                continue;
+            }
+
+            if unescape && c == B_SLASH && chars.peek() == Some(&R_BRACE) {
+                chars.next();
+                new_val.push(R_BRACE);
+                continue;
+            }
+
+            if c != DOLLAR {
+                new_val.push(c);
+                continue;
+            }
+
+            match chars.peek() {
+                Some(&L_BRACE) => {
+                    chars.next();
+
+                    match Self::braced(&mut chars) {
+                        Ok(inner) => new_val.push_str(&self.resolve(&inner)),
+                        // Not closed, so it is not a variable
+                        Err(inner) => {
+                            new_val.push(DOLLAR);
+                            new_val.push(L_BRACE);
+                            new_val.push_str(&inner);
+                        }
+                    }
+                }
+                // Positional parameters i.e. `$1`, which are never set
+                Some(x) if bash && x.is_ascii_digit() => {
+                    chars.next();
+                }
+                Some(x) if self.is_ident(x) => {
+                    let mut key = String::new();
+
+                    while let Some(x) = chars.next_if(|x| self.is_ident(x)) {
+                        key.push(x);
+                    }
+
+                    new_val.push_str(&self.lookup_or_empty(&key));
+                }
+                _ => new_val.push(DOLLAR),
+            }
+        }
+
+        new_val
+    }
+
+    // Substitutes the variables which are enclosed by the sigil i.e. `%VAR%`
+    fn expand_enclosed(&self, val: &str, sigil: char) -> String {
+        let mut new_val = String::with_capacity(val.len());
+        let mut rest = val;
+
+        while let Some(start) = rest.find(sigil) {
+            new_val.push_str(&rest[..start]);
+            rest = &rest[start + sigil.len_utf8()..];
+
+            match rest.find(sigil) {
+                // Doubled i.e. `%%` is a literal `%`
+                Some(0) => {
+                    new_val.push(sigil);
+                    rest = &rest[sigil.len_utf8()..];
+                }
+                Some(end) if self.is_enclosed(&rest[..end]) => {
+                    new_val.push_str(&self.resolve(&rest[..end]));
+                    rest = &rest[end + sigil.len_utf8()..];
+                }
+                // Not a variable, so the closing sigil can still start one
+                _ => new_val.push(sigil),
+            }
+        }
+
+        new_val.push_str(rest);
+        new_val
+    }
+
+    // Whether the text between the sigils is a name, followed by an operation if any
+    fn is_enclosed(&self, inner: &str) -> bool {
+        let name = inner.strip_prefix(BANG).unwrap_or(inner);
+        let idx = name
+            .find(|c: char| !self.is_ident(&c))
+            .unwrap_or(name.len());
+
+        idx > 0
+            && match name[idx..].chars().next() {
+                Some(c) => c == COLON || c == DASH || c == SLASH,
+                None => true,
+            }
+    }
+
+    // Parses `offset` or `offset:length` of the substring
+    fn substring(op: &str) -> Option<(usize, Option<usize>)> {
+        match op.split_once(COLON) {
+            Some((offset, length)) => Some((offset.parse().ok()?, Some(length.parse().ok()?))),
+            None => Some((op.parse().ok()?, None)),
+        }
+    }
+
+    // Collects everything till the matching `}`, and returns `Err` if it is not closed. An
+    // escaped `\}` is kept as it is, and is never the closing brace.
+    fn braced(chars: &mut Peekable<Chars>) -> Result<String, String> {
+        let mut inner = String::new();
+        let mut depth = 0;
+
+        while let Some(c) = chars.next() {
+            match c {
+                B_SLASH if chars.peek() == Some(&R_BRACE) => {
+                    inner.push(c);
+                    inner.extend(chars.next());
+                    continue;
+                }
+                L_BRACE => depth += 1,
+                R_BRACE if depth == 0 => return Ok(inner),
+                R_BRACE => depth -= 1,
+                _ => {}
+            }
+            inner.push(c);
+        }
+
+        Err(inner)
+    }
+
+    // Resolves the content of `${...}`
+    fn resolve(&self, inner: &str) -> String {
+        // Indirection i.e. ${!NAME}, the value of NAME is the name of the variable
+        if let Some(name) = inner.strip_prefix(BANG) {
+            return match self.lookup(name) {
+                Some(x) => self.lookup_or_empty(&x),
+                None => self.lookup_or_empty(name),
+            };
+        }
+
+        let idx = inner
+            .find(|c: char| !self.is_ident(&c))
+            .unwrap_or(inner.len());
+        let (name, op) = inner.split_at(idx);
+
+        // Default i.e. ${VAR:-default} if the variable is not defined or empty, or ${VAR-default}
+        // if it is not defined
+        let default = match op.strip_prefix(COLON).unwrap_or(op).strip_prefix(DASH) {
+            Some(default) if !name.is_empty() => Some((op.starts_with(COLON), default)),
+            _ => None,
+        };
+
+        if let Some((or_empty, default)) = default {
+            let or_empty = or_empty || self.opts.compat == Compat::Zenv;
+
+            return match self.lookup(name) {
+                Some(val) if !(or_empty && val.is_empty()) => val,
+                _ => self.expand_inner(default, true),
+            };
+        }
+
+        // Substring i.e. ${VAR:offset} or ${VAR:offset:length}, in characters
+        if let Some((offset, length)) = op.strip_prefix(COLON).and_then(Self::substring) {
+            let val = self.lookup_or_empty(name);
+            let chars = val.chars().skip(offset);
+
+            return match length {
+                Some(length) => chars.take(length).collect(),
+                None => chars.collect(),
+            };
+        }
+
+        // Replacement i.e. ${VAR/search/replace}, or ${VAR//search/replace} for every match
+        if let Some(op) = op.strip_prefix(SLASH) {
+            let val = self.lookup_or_empty(name);
+
+            let (all, op) = match op.strip_prefix(SLASH) {
+                Some(op) => (true, op),
+                None => (false, op),
+            };
+
+            // Without the replacement, the matches are removed
+            let (search, replace) = op.split_once(SLASH).unwrap_or((op, ""));
+            let (search, replace) = (search.replace("\\}", "}"), replace.replace("\\}", "}"));
+
+            return match search.as_str() {
+                "" => val,
+                _ if all => val.replace(&search, &replace),
+                _ => val.replacen(&search, &replace, 1),
+            };
+        }
+
+        self.lookup_or_empty(inner)
+    }
+}
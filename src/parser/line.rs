@@ -1,11 +1,16 @@
+use alloc::string::{String, ToString};
+
+use super::options::ParseOptions;
+
 const LF: char = '\n';
 const HASH: char = '#';
 const B_SLASH: char = '\\';
 const S_QUOTE: char = '\'';
 const D_QUOTE: char = '"';
+const B_TICK: char = '`';
 
 /// Type of the quote
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Quote {
     /// When the value is single quoted i.e. `'`
     Single,
@@ -13,12 +18,15 @@ pub enum Quote {
     /// When the value is double quoted i.e. `"`
     Double,
 
+    /// When the value is wrapped in backticks i.e. `` ` ``. Treated literally, same as [`Quote::Single`]
+    Backtick,
+
     /// When the value is not quoted
     No,
 }
 
 /// To collect the info about the current line
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyVal<'k> {
     /// `key` of the variable
     pub k: &'k str,
@@ -54,12 +62,17 @@ pub struct KeyVal<'k> {
 /// let v = "single_quoted".to_string();
 /// assert_eq!(quoted, Line::KeyVal(KeyVal { k, v, q: Quote::Single }));
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Line<'l> {
     /// When the current line is a `key=val` pair
     KeyVal(KeyVal<'l>),
 
-    /// When the current line is empty
+    /// When the current line is a `key+=val` pair i.e. the value is appended to any previous
+    /// value of the key, or is set if there is none
+    Append(KeyVal<'l>),
+
+    /// When the current line is empty, a comment or doesn't have any `=` i.e. `KEY`. Whereas
+    /// `KEY=` is a [`Line::KeyVal`] with an empty value.
     Empty,
 }
 
@@ -104,78 +117,203 @@ impl<'l> Line<'l> {
         }
     }
 
-    fn retain_quote(orgnl: &str, after: String, q: Quote) -> (String, Quote) {
+    // Removes the comment, if any, from the unquoted value
+    pub(crate) fn strip_comment(val: &str, require_space: bool) -> &str {
+        let mut prev = None;
+
+        for (i, c) in val.char_indices() {
+            if c == HASH && (!require_space || prev.is_some_and(char::is_whitespace)) {
+                return &val[..i];
+            }
+            prev = Some(c);
+        }
+
+        val
+    }
+
+    // The quoted part of the value with the quotes, if the quote is closed
+    fn quoted(val: &str) -> Option<(Quote, &str)> {
+        let c = val.chars().next()?;
+
+        let q = match c {
+            D_QUOTE => Quote::Double,
+            S_QUOTE => Quote::Single,
+            B_TICK => Quote::Backtick,
+            _ => return None,
+        };
+
+        val[1..].find(c).map(|i| (q, &val[..i + 2]))
+    }
+
+    fn retain_quote(orgnl: &str, after: String, q: Quote, opts: &ParseOptions) -> (String, Quote) {
         // If both strings length matches then it is not closed
         if orgnl.len().eq(&(after.len() + 1)) {
-            let new_val: String = orgnl.chars().take_while(|c| c != &HASH).collect();
+            let new_val = Self::strip_comment(orgnl, opts.hash_comments_require_space);
 
             (new_val.trim().to_string(), Quote::No)
         } else {
             (after, q)
         }
     }
-}
 
-impl<'l> From<&'l str> for Line<'l> {
-    fn from(line: &'l str) -> Self {
-        if line.is_empty() || line.starts_with(HASH) {
+    /// Parses the line with the provided [`ParseOptions`]. [`Line::from`] is same as parsing
+    /// with the default options.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Line, KeyVal, ParseOptions, Quote};
+    ///
+    /// let opts = ParseOptions {
+    ///     hash_comments_require_space: false,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let line = Line::parse("COLOR=#fff", &opts);
+    ///
+    /// let k = "COLOR";
+    /// let v = "".to_string();
+    /// assert_eq!(line, Line::KeyVal(KeyVal { k, v, q: Quote::No }));
+    /// ```
+    pub fn parse(line: &'l str, opts: &ParseOptions) -> Self {
+        if line.is_empty() || line.trim_start().starts_with(HASH) {
             return Self::Empty;
         };
 
-        let mut parts = line.splitn(2, '=');
+        match Self::split(line, opts) {
+            Some((k, append, v)) => {
+                let (q, v) = parse_value(v, opts);
+                let kv = KeyVal { k, v, q };
 
-        match (parts.next(), parts.next()) {
-            (Some(k), Some(v)) => {
-                let key = k.trim();
-                let mut chars = v.chars();
+                if append {
+                    Line::Append(kv)
+                } else {
+                    Line::KeyVal(kv)
+                }
+            }
+            None => Self::Empty,
+        }
+    }
 
-                let first = chars.next();
+    // Splits the line into the key, whether it is a `+=` line and the raw value
+    pub(crate) fn split(line: &'l str, opts: &ParseOptions) -> Option<(&'l str, bool, &'l str)> {
+        match Self::quoted_key(line).filter(|_| opts.allow_quoted_keys) {
+            // The key is taken as it is, only the `+` can be between the quote and the `=`
+            Some((k, rest)) => rest.split_once('=').map(|(sep, v)| (k, sep == "+", v)),
+            None => line.split_once('=').map(|(k, v)| {
+                // `KEY+=val` appends to the previous value
+                match k.trim_end().strip_suffix('+') {
+                    Some(k) => (k.trim(), true, v),
+                    None => (k.trim(), false, v),
+                }
+            }),
+        }
+    }
 
-                match first {
-                    Some(D_QUOTE) => {
-                        let val = {
-                            let v: String = chars.take_while(|x| x != &D_QUOTE).collect();
-                            Self::replace_lf(&v)
-                        };
+    // Text between the closing quote and the comment i.e. ` b` of `"a" b # comment`, if any
+    pub(crate) fn trailing<'v>(val: &'v str, opts: &ParseOptions) -> Option<&'v str> {
+        let (_, quoted) = Self::quoted(val)?;
+        let rest = &val[quoted.len()..];
 
-                        let (v, q) = Self::retain_quote(v, val, Quote::Double);
+        // A `#` right after the closing quote is always a comment
+        if rest.trim_start().starts_with(HASH) {
+            return None;
+        }
 
-                        Line::KeyVal(KeyVal { k: key, v, q })
-                    }
-                    Some(S_QUOTE) => {
-                        let val: String = chars
-                            .take_while(|x| x != &S_QUOTE)
-                            .map(Self::escape_lf)
-                            .collect();
+        let rest = Self::strip_comment(rest, opts.hash_comments_require_space).trim_end();
 
-                        let (v, q) = Self::retain_quote(v, val, Quote::Single);
+        Some(rest).filter(|x| !x.is_empty())
+    }
 
-                        Line::KeyVal(KeyVal { k: key, v, q })
-                    }
-                    Some(a) => {
-                        let mut val = Self::escape_lf(a);
-
-                        val.push_str(
-                            &chars
-                                .take_while(|x| x != &HASH)
-                                .map(Self::escape_lf)
-                                .collect::<String>(),
-                        );
-
-                        Line::KeyVal(KeyVal {
-                            k: key,
-                            v: val.trim().to_string(),
-                            q: Quote::No,
-                        })
-                    }
-                    _ => Line::KeyVal(KeyVal {
-                        k: key,
-                        v: String::with_capacity(0),
-                        q: Quote::No,
-                    }),
-                }
-            }
-            _ => Self::Empty,
+    // Splits `"KEY"=val` or `'KEY'=val` into the key without the quotes and the rest of the line
+    // after the closing quote, if that is followed by `=` or `+=`
+    pub(crate) fn quoted_key(line: &str) -> Option<(&str, &str)> {
+        let line = line.trim_start();
+        let q = line
+            .chars()
+            .next()
+            .filter(|c| c == &D_QUOTE || c == &S_QUOTE)?;
+
+        let (k, rest) = line[1..].split_once(q)?;
+        let rest = rest.trim_start();
+
+        if k.is_empty() || !(rest.starts_with('=') || rest.starts_with("+=")) {
+            return None;
+        }
+
+        Some((k, rest))
+    }
+}
+
+/// Parses the value of a `KEY=value` line i.e. everything after the first `=`, with the same rules
+/// as [`Line::parse`]. This detects the quotes, handles the escapes of the double quoted values and
+/// removes the comment from the unquoted values. Any text between the closing quote and the
+/// comment is appended literally i.e. `"a" b` is `a b`, which is an error with
+/// [`ParseOptions::strict`] when parsing the lines. No variable substitution is done.
+///
+/// Example
+/// ```
+/// use zenv::{parse_value, ParseOptions, Quote};
+///
+/// let opts = ParseOptions::default();
+///
+/// assert_eq!(parse_value("basic # comment", &opts), (Quote::No, "basic".to_string()));
+/// assert_eq!(parse_value("'single' # comment", &opts), (Quote::Single, "single".to_string()));
+/// assert_eq!(parse_value("\"multi\\nline\"", &opts), (Quote::Double, "multi\nline".to_string()));
+/// ```
+pub fn parse_value(v: &str, opts: &ParseOptions) -> (Quote, String) {
+    // Lenient about the text after the closing quote, which is kept literally
+    let rest = Line::trailing(v, opts).unwrap_or_default();
+
+    if opts.keep_quotes {
+        if let Some((q, v)) = Line::quoted(v) {
+            return (q, [v, rest].concat());
         }
     }
+
+    let mut chars = v.chars();
+
+    let (v, q) = match chars.next() {
+        Some(D_QUOTE) => {
+            let val = {
+                let v: String = chars.take_while(|x| x != &D_QUOTE).collect();
+                Line::replace_lf(&v)
+            };
+
+            Line::retain_quote(v, val, Quote::Double, opts)
+        }
+        Some(S_QUOTE) => {
+            let val: String = chars
+                .take_while(|x| x != &S_QUOTE)
+                .map(Line::escape_lf)
+                .collect();
+
+            Line::retain_quote(v, val, Quote::Single, opts)
+        }
+        Some(B_TICK) => {
+            let val: String = chars
+                .take_while(|x| x != &B_TICK)
+                .map(Line::escape_lf)
+                .collect();
+
+            Line::retain_quote(v, val, Quote::Backtick, opts)
+        }
+        Some(_) => {
+            let val: String = Line::strip_comment(v, opts.hash_comments_require_space)
+                .trim()
+                .chars()
+                .map(Line::escape_lf)
+                .collect();
+
+            (val, Quote::No)
+        }
+        None => (String::with_capacity(0), Quote::No),
+    };
+
+    (q, v + rest)
+}
+
+impl<'l> From<&'l str> for Line<'l> {
+    fn from(line: &'l str) -> Self {
+        Self::parse(line, &ParseOptions::default())
+    }
 }
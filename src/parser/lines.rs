@@ -1,24 +1,209 @@
-use std::{collections::HashMap, env};
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
-use super::line::{KeyVal, Line, Quote};
+use super::{
+    error::ParseError,
+    line::{KeyVal, Line, Quote},
+    options::ParseOptions,
+};
+#[cfg(feature = "std")]
+use super::{expand::Expander, options::ExpandOptions};
+
+const HEREDOC: &str = "<<";
+const DIRECTIVE: &str = "zenv:";
+
+/// How the keys should be normalized by [`Lines::transform_keys`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeyTransform {
+    /// Convert the keys to uppercase
+    Upper,
+
+    /// Convert the keys to lowercase
+    Lower,
+
+    /// Keep the keys as they are
+    #[default]
+    None,
+}
+
+impl FromStr for KeyTransform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            "none" => Ok(Self::None),
+            x => Err(format!(
+                "Invalid key transform `{}`, expected one of upper, lower or none",
+                x
+            )),
+        }
+    }
+}
 
 /// `Lines` is used to parse the sequence of lines
 ///
 /// [`Zenv`](crate::Zenv) is built on top it. And if you want you can directly use this but don't :)
-#[derive(Debug)]
+///
+/// Two `Lines` are equal if they have the same pairs in the same order, regardless of the empty
+/// lines and the comments between them.
+///
+/// Example
+/// ```
+/// use zenv::{Line, Lines};
+///
+/// let lines = Lines::from("# comment\nBASIC=basic\n\nQUOTED='quoted'");
+/// let expected = Lines::new(vec![Line::from("BASIC=basic"), Line::from("QUOTED='quoted'")]);
+///
+/// assert_eq!(lines, expected);
+/// assert_ne!(lines, Lines::from("QUOTED='quoted'\nBASIC=basic"));
+/// ```
+#[derive(Debug, Default)]
 pub struct Lines<'l> {
-    lines: Vec<KeyVal<'l>>,
+    lines: Vec<Pair<'l>>,
+}
+
+impl PartialEq for Lines<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.lines.len() == other.lines.len()
+            && self
+                .lines
+                .iter()
+                .zip(&other.lines)
+                .all(|(a, b)| a.kv == b.kv && a.append == b.append)
+    }
+}
+
+impl Eq for Lines<'_> {}
+
+/// A single parsed `key=val` or `key+=val` pair, returned by [`Lines::entries`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Entry<'e> {
+    /// `key` of the variable
+    pub key: &'e str,
+
+    /// `value` of the variable as it is written in the file, after removing the quotes and the
+    /// comment but before any substitution or appending
+    pub raw: &'e str,
+
+    /// Type of the quote around the value
+    pub quote: Quote,
+
+    /// Whether the value is appended to the previous value i.e. `key+=val`
+    pub append: bool,
+
+    /// Line number where the pair starts, starting from 1
+    pub line: usize,
+
+    /// Line number where the pair ends, which is only after `line` for a heredoc or a continued
+    /// value
+    pub end: usize,
+}
+
+// Same as `KeyVal`, but the key is owned if it is not borrowed from the source i.e. transformed
+#[derive(Debug, Clone, PartialEq)]
+struct Var<'l> {
+    k: Cow<'l, str>,
+    v: String,
+    q: Quote,
+}
+
+impl<'l> From<KeyVal<'l>> for Var<'l> {
+    fn from(kv: KeyVal<'l>) -> Self {
+        Self {
+            k: Cow::Borrowed(kv.k),
+            v: kv.v,
+            q: kv.q,
+        }
+    }
+}
+
+// A single `key=val` or `key+=val` pair
+#[derive(Debug, Clone)]
+struct Pair<'l> {
+    kv: Var<'l>,
+    append: bool,
+
+    // Line numbers where the pair starts and ends
+    line: usize,
+    end: usize,
+}
+
+impl<'l> Pair<'l> {
+    fn from_line(line: Line<'l>, idx: usize) -> Option<Self> {
+        let (kv, append) = match line {
+            Line::KeyVal(kv) => (kv, false),
+            Line::Append(kv) => (kv, true),
+            Line::Empty => return None,
+        };
+
+        Some(Self {
+            kv: kv.into(),
+            append,
+            line: idx + 1,
+            end: idx + 1,
+        })
+    }
+
+    // Owns the key, for the pairs which are not borrowed from the source
+    fn into_owned<'o>(self) -> Pair<'o> {
+        Pair {
+            kv: Var {
+                k: Cow::Owned(self.kv.k.into_owned()),
+                v: self.kv.v,
+                q: self.kv.q,
+            },
+            append: self.append,
+            line: self.line,
+            end: self.end,
+        }
+    }
+
+    fn entry(&self) -> Entry<'_> {
+        Entry {
+            key: &self.kv.k,
+            raw: &self.kv.v,
+            quote: self.kv.q,
+            append: self.append,
+            line: self.line,
+            end: self.end,
+        }
+    }
+
+    // Returns the value after appending it to the previous value of the key, if needed
+    fn resolve(&self, val: &str, prev: Option<&String>) -> String {
+        match prev {
+            Some(prev) if self.append => format!("{}{}", prev, val),
+            _ => val.to_string(),
+        }
+    }
+}
+
+// A `# zenv:...` comment
+enum Directive<'d> {
+    // `# zenv:if KEY=value`, the condition is `None` if it is malformed
+    If(Option<(&'d str, &'d str)>),
+
+    // `# zenv:endif`
+    EndIf,
 }
 
 impl<'l> From<&'l str> for Lines<'l> {
     fn from(lines: &'l str) -> Self {
-        let lines: Vec<KeyVal> = lines
+        let lines = lines
             .lines()
-            .into_iter()
-            .filter_map(|x| match Line::from(x) {
-                Line::KeyVal(x) => Some(x),
-                _ => None,
-            })
+            .enumerate()
+            .filter_map(|(idx, x)| Pair::from_line(Line::from(x), idx))
             .collect();
 
         Self { lines }
@@ -26,6 +211,376 @@ impl<'l> From<&'l str> for Lines<'l> {
 }
 
 impl<'l> Lines<'l> {
+    /// Parses the lines with the provided [`ParseOptions`]. Unlike [`Lines::from`], this fails
+    /// if the content can't be parsed, like a heredoc without the closing delimiter, a NUL byte
+    /// anywhere in the content, or any invalid line if [`ParseOptions::strict`] is enabled.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Lines, ParseOptions};
+    ///
+    /// const LINES: &str = "BASIC=basic\nHEREDOC<<EOF\nmulti\nline\nEOF";
+    ///
+    /// let opts = ParseOptions {
+    ///     heredoc: true,
+    ///     ..Default::default()
+    /// };
+    /// let parsed = Lines::parse(LINES, &opts).unwrap().to_btree_map();
+    ///
+    /// assert_eq!(parsed.get("BASIC").unwrap(), &"basic".to_string());
+    /// assert_eq!(parsed.get("HEREDOC").unwrap(), &"multi\nline".to_string());
+    ///
+    /// let err = Lines::parse("HEREDOC<<EOF\nnot closed", &opts).unwrap_err();
+    ///
+    /// assert_eq!(err.line, 1);
+    /// ```
+    pub fn parse(src: &'l str, opts: &ParseOptions) -> Result<Self, ParseError> {
+        Self::parse_dropped(src, opts, &mut Vec::new())
+    }
+
+    /// Same as [`Lines::parse`], but doesn't stop at the first error. The invalid line is
+    /// skipped and the parsing continues with the next line, so every error is returned in the
+    /// order of the lines. This is empty if the content can be parsed.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Lines, ParseOptions};
+    ///
+    /// let opts = ParseOptions {
+    ///     strict: true,
+    ///     ..Default::default()
+    /// };
+    /// let errors = Lines::errors("1BAD=x\nGOOD=good\nnot a pair", &opts);
+    ///
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].line, 1);
+    /// assert_eq!(errors[1].line, 3);
+    /// ```
+    pub fn errors(src: &'l str, opts: &ParseOptions) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+
+        Self::parse_recover(src, opts, &mut Vec::new(), &mut errors);
+
+        // The unterminated blocks are only known at the end
+        errors.sort_by_key(|x| x.line);
+
+        errors
+    }
+
+    // Same as `parse`, but also collects the invalid lines which are dropped as `(line, content)`
+    pub(crate) fn parse_dropped(
+        src: &'l str,
+        opts: &ParseOptions,
+        dropped: &mut Vec<(usize, &'l str)>,
+    ) -> Result<Self, ParseError> {
+        let mut errors = Vec::new();
+        let lines = Self::parse_recover(src, opts, dropped, &mut errors);
+
+        match errors.into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(lines),
+        }
+    }
+
+    // Parses the valid lines and collects the errors instead of stopping at the first one
+    fn parse_recover(
+        src: &'l str,
+        opts: &ParseOptions,
+        dropped: &mut Vec<(usize, &'l str)>,
+        errors: &mut Vec<ParseError>,
+    ) -> Self {
+        // Can't be passed to a process, so failing early instead of when spawning
+        for (idx, line) in src.lines().enumerate().filter(|(_, x)| x.contains('\0')) {
+            errors.push(ParseError::new(idx + 1, line, "NUL byte is not allowed"));
+        }
+
+        if !errors.is_empty() {
+            return Self { lines: Vec::new() };
+        }
+
+        let mut lines = Vec::new();
+        let mut src = src.lines().enumerate();
+
+        // Open conditional blocks as `(index, line, condition)`
+        let mut blocks: Vec<(usize, &str, bool)> = Vec::new();
+
+        while let Some((idx, line)) = src.next() {
+            let active = blocks.iter().all(|x| x.2);
+
+            if opts.conditionals {
+                match Self::directive(line) {
+                    Some(Directive::If(Some((key, val)))) => {
+                        let found = Self::value_of(&lines, key).or_else(|| Self::env_var(key));
+
+                        blocks.push((idx, line, found.as_deref() == Some(val)));
+                        continue;
+                    }
+                    Some(Directive::If(None)) => {
+                        errors.push(ParseError::new(
+                            idx + 1,
+                            line,
+                            "Expected `# zenv:if KEY=value`",
+                        ));
+                        continue;
+                    }
+                    Some(Directive::EndIf) => {
+                        if blocks.pop().is_none() {
+                            errors.push(ParseError::new(
+                                idx + 1,
+                                line,
+                                "Unexpected `# zenv:endif` without `# zenv:if`",
+                            ));
+                        }
+                        continue;
+                    }
+                    None => {}
+                }
+            }
+
+            if opts.heredoc {
+                if let Some((key, delim)) = Self::heredoc(line) {
+                    let mut body: Vec<&str> = Vec::new();
+                    let mut closed = None;
+
+                    for (end, x) in src.by_ref() {
+                        if x.trim_end() == delim {
+                            closed = Some(end);
+                            break;
+                        }
+                        body.push(x);
+                    }
+
+                    // The rest of the content is already consumed by the heredoc
+                    let Some(end) = closed else {
+                        errors.push(ParseError::new(
+                            idx + 1,
+                            line,
+                            format!("Unterminated heredoc, expected `{}`", delim),
+                        ));
+                        break;
+                    };
+
+                    if opts.strict && !Self::is_valid_key(key, opts) {
+                        errors.push(ParseError::new(idx + 1, line, "Invalid key"));
+                        continue;
+                    }
+
+                    if active {
+                        lines.push(Pair {
+                            kv: Var {
+                                k: Cow::Borrowed(key),
+                                v: body.join("\n"),
+                                q: Quote::No,
+                            },
+                            append: false,
+                            line: idx + 1,
+                            end: end + 1,
+                        });
+                    }
+
+                    continue;
+                }
+            }
+
+            let quoted_key = opts.allow_quoted_keys && Line::quoted_key(line).is_some();
+
+            // Quoted values are never continued, so only the current line is checked
+            if opts.strict {
+                if let Some(rest) =
+                    Line::split(line, opts).and_then(|(_, _, v)| Line::trailing(v, opts))
+                {
+                    errors.push(ParseError::new(
+                        idx + 1,
+                        line,
+                        format!("Unexpected `{}` after the closing quote", rest.trim()),
+                    ));
+                    continue;
+                }
+            }
+
+            let pair = match Self::continued(line, &mut src, opts) {
+                Some((joined, count)) => Pair::from_line(Line::parse(&joined, opts), idx)
+                    .map(|x| Pair {
+                        end: x.line + count,
+                        ..x
+                    })
+                    .map(Pair::into_owned),
+                None => Pair::from_line(Line::parse(line, opts), idx),
+            };
+
+            match pair {
+                Some(x) if opts.strict && !Self::is_valid_key(&x.kv.k, opts) && !quoted_key => {
+                    errors.push(ParseError::new(idx + 1, line, "Invalid key"));
+                    continue;
+                }
+                Some(x) if active => lines.push(x),
+                Some(_) => {}
+                None if opts.strict && !Self::is_blank(line) => {
+                    errors.push(ParseError::new(idx + 1, line, "Expected `KEY=value`"));
+                    continue;
+                }
+                None if active && !Self::is_blank(line) => dropped.push((idx + 1, line)),
+                None => {}
+            }
+        }
+
+        // Innermost first, same as the stack order
+        for (idx, line, _) in blocks.into_iter().rev() {
+            errors.push(ParseError::new(
+                idx + 1,
+                line,
+                "Unterminated `# zenv:if`, expected `# zenv:endif`",
+            ));
+        }
+
+        Self { lines }
+    }
+
+    // Joins the following lines if the line is continued with a trailing `\`, along with the
+    // number of the joined lines
+    fn continued<'s>(
+        line: &str,
+        src: &mut impl Iterator<Item = (usize, &'s str)>,
+        opts: &ParseOptions,
+    ) -> Option<(String, usize)> {
+        if !opts.line_continuation || !Self::is_continued(line, opts) {
+            return None;
+        }
+
+        let mut joined = line.trim_end().to_string();
+        let mut count = 0;
+
+        while Self::is_continued(&joined, opts) {
+            // Kept literally if there is nothing to continue with
+            let Some((_, next)) = src.next() else { break };
+
+            joined = joined.trim_end().to_string();
+            joined.pop();
+            joined.push_str(next);
+            count += 1;
+        }
+
+        Some((joined, count))
+    }
+
+    // Whether the line ends with a `\` which is also the end of an unquoted value
+    fn is_continued(line: &str, opts: &ParseOptions) -> bool {
+        if !line.trim_end().ends_with('\\') {
+            return false;
+        }
+
+        match Line::parse(line, opts) {
+            Line::KeyVal(kv) | Line::Append(kv) if kv.q == Quote::No => {
+                kv.v.ends_with('\\') && !kv.v.ends_with("\\\\")
+            }
+            _ => false,
+        }
+    }
+
+    // Returns the directive if the line is a `# zenv:...` comment
+    fn directive(line: &str) -> Option<Directive<'_>> {
+        let rest = line
+            .trim()
+            .strip_prefix('#')?
+            .trim_start()
+            .strip_prefix(DIRECTIVE)?;
+
+        if rest == "endif" {
+            return Some(Directive::EndIf);
+        }
+
+        let cond = rest
+            .strip_prefix("if")
+            .filter(|x| x.starts_with(char::is_whitespace))?;
+
+        // `KEY==value` is also allowed
+        let cond = cond
+            .split_once('=')
+            .map(|(k, v)| (k.trim(), v.strip_prefix('=').unwrap_or(v).trim()))
+            .filter(|(k, _)| !k.is_empty());
+
+        Some(Directive::If(cond))
+    }
+
+    // Falls back to the running process for the conditions, which is only known with `std`
+    #[cfg(feature = "std")]
+    fn env_var(key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn env_var(_: &str) -> Option<String> {
+        None
+    }
+
+    // Returns the value of the key, after appending the `key+=val` pairs
+    fn value_of(lines: &[Pair], key: &str) -> Option<String> {
+        lines
+            .iter()
+            .filter(|x| x.kv.k == key)
+            .fold(None, |prev, line| match prev {
+                Some(prev) if line.append => Some(prev + &line.kv.v),
+                _ => Some(line.kv.v.clone()),
+            })
+    }
+
+    fn is_valid_key(key: &str, opts: &ParseOptions) -> bool {
+        let key = match Self::split_index(key) {
+            Some((name, _)) if opts.allow_brackets => name,
+            _ => key,
+        };
+
+        let mut chars = key.chars();
+
+        match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| {
+                c.is_ascii_alphanumeric() || c == '_' || (opts.relaxed_keys && ".-/".contains(c))
+            }),
+            _ => false,
+        }
+    }
+
+    // Splits `KEY[0]` into the name and the index
+    fn split_index(key: &str) -> Option<(&str, usize)> {
+        let (name, idx) = key.strip_suffix(']')?.split_once('[')?;
+
+        if idx.is_empty() || !idx.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        Some((name, idx.parse().ok()?))
+    }
+
+    // Whether the line is empty or a comment
+    fn is_blank(line: &str) -> bool {
+        let line = line.trim();
+
+        line.is_empty() || line.starts_with('#')
+    }
+
+    // Returns the key and the delimiter if the line starts a heredoc
+    fn heredoc(line: &str) -> Option<(&str, &str)> {
+        let line = line.trim();
+
+        if line.starts_with('#') {
+            return None;
+        }
+
+        let idx = line.find(HEREDOC)?;
+        let (key, delim) = (&line[..idx], line[idx + HEREDOC.len()..].trim());
+
+        let key = key.trim_end();
+        let key = key.strip_suffix('=').unwrap_or(key).trim_end();
+
+        let valid = |x: &str| !x.is_empty() && !x.contains(|c: char| c == '=' || c.is_whitespace());
+
+        if valid(key) && valid(delim) {
+            Some((key, delim))
+        } else {
+            None
+        }
+    }
+
     /// Create `Lines` from a vec of [`Line`]. Can be useful if you manually parsing individual lines
     ///
     /// Example
@@ -38,7 +593,7 @@ impl<'l> Lines<'l> {
     ///     Line::from("QUOTED='quoted'")
     /// ];
     ///
-    /// let parsed = Lines::new(lines).to_hash_map();
+    /// let parsed = Lines::new(lines).to_btree_map();
     ///
     /// assert_eq!(parsed.get("BASIC").unwrap(), &"basic".to_string());
     /// assert_eq!(parsed.get("QUOTED").unwrap(), &"quoted".to_string());
@@ -46,15 +601,405 @@ impl<'l> Lines<'l> {
     pub fn new(lines: Vec<Line<'l>>) -> Self {
         let lines = lines
             .into_iter()
-            .filter_map(|x| match x {
-                Line::KeyVal(x) => Some(x),
-                _ => None,
+            .enumerate()
+            .filter_map(|(idx, x)| Pair::from_line(x, idx))
+            .collect();
+
+        Self { lines }
+    }
+
+    /// Create `Lines` from the lines which are already split i.e. a `Vec<String>`, same as
+    /// joining them and using [`Lines::from`] but without the copy. Every line is parsed with
+    /// [`Line::from`], so the order is kept and the empty lines and the comments are skipped. The
+    /// keys are owned, as the lines can be dropped right after.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let received = vec![
+    ///     "BASIC=basic".to_string(),
+    ///     "# COMMENT=commented".to_string(),
+    ///     "LIST+=,appended".to_string(),
+    /// ];
+    ///
+    /// let lines = Lines::from_lines(received);
+    ///
+    /// assert_eq!(lines, Lines::from("BASIC=basic\n# COMMENT=commented\nLIST+=,appended"));
+    /// assert_eq!(lines.get("BASIC"), Some("basic".to_string()));
+    /// ```
+    pub fn from_lines(lines: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, x)| {
+                Pair::from_line(Line::from(x.as_ref()), idx).map(Pair::into_owned)
             })
             .collect();
 
         Self { lines }
     }
 
+    /// Create an empty `Lines`, same as [`Lines::default`], to be filled with [`Lines::extend`]
+    /// or [`Lines::merge`]
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{KeyVal, Lines, Quote};
+    ///
+    /// let mut lines = Lines::empty();
+    ///
+    /// assert!(lines.is_empty());
+    ///
+    /// lines.extend(vec![KeyVal {
+    ///     k: "BASIC",
+    ///     v: "basic".to_string(),
+    ///     q: Quote::No,
+    /// }]);
+    ///
+    /// assert_eq!(lines.get("BASIC"), Some("basic".to_string()));
+    /// ```
+    pub fn empty() -> Self {
+        Self { lines: Vec::new() }
+    }
+
+    /// Create `Lines` from the `(key, value)` pairs, in the same order, without parsing them. The
+    /// values are taken as they are i.e. [`Quote::No`], so they are not substituted by
+    /// [`Lines::expand`].
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let pairs = vec![
+    ///     ("BASIC".to_string(), "basic".to_string()),
+    ///     ("NOT_EXPANDED".to_string(), "${BASIC}".to_string()),
+    /// ];
+    ///
+    /// let lines = Lines::from_pairs(pairs.clone());
+    ///
+    /// assert_eq!(lines.to_vec(), pairs);
+    /// # #[cfg(feature = "std")]
+    /// assert_eq!(lines.expand().get("NOT_EXPANDED").unwrap(), "${BASIC}");
+    /// ```
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (String, String)>) -> Self {
+        let lines = pairs
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (k, v))| Pair {
+                kv: Var {
+                    k: Cow::Owned(k),
+                    v,
+                    q: Quote::No,
+                },
+                append: false,
+                line: idx + 1,
+                end: idx + 1,
+            })
+            .collect();
+
+        Self { lines }
+    }
+
+    /// Merges the lines of `other` into `self`, like a file which is loaded after the current
+    /// one. Every `key=val` pair of `other` replaces the previous pairs of the same key, while
+    /// a `key+=val` pair is appended to the previous value, or is set if there is none.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let mut lines = Lines::from("BASIC=basic\nLIST=a\nLIST+=,b");
+    /// lines.merge(Lines::from("BASIC=overridden\nLIST+=,c\nNEW+=new"));
+    ///
+    /// let parsed = lines.to_btree_map();
+    ///
+    /// assert_eq!(lines.len(), 5);
+    /// assert_eq!(parsed.get("BASIC").unwrap(), &"overridden".to_string());
+    /// assert_eq!(parsed.get("LIST").unwrap(), &"a,b,c".to_string());
+    /// assert_eq!(parsed.get("NEW").unwrap(), &"new".to_string());
+    /// ```
+    pub fn merge(&mut self, other: Lines<'l>) {
+        for pair in other.lines {
+            if !pair.append {
+                self.lines.retain(|x| x.kv.k != pair.kv.k);
+            }
+            self.lines.push(pair);
+        }
+    }
+
+    /// Appends the pairs at the end, as they are. Unlike [`Lines::merge`], the previous pairs of
+    /// the same keys are kept, so the conversions still pick the last pair as the value like any
+    /// other duplicate key, and the order of the pairs is exactly the order of the calls. Every
+    /// pair is a `key=val` pair, where the line number is its position in `pairs`.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{KeyVal, Lines, Quote};
+    ///
+    /// let mut lines = Lines::from("BASIC=basic\nLIST=a");
+    ///
+    /// lines.extend(vec![KeyVal {
+    ///     k: "BASIC",
+    ///     v: "extended".to_string(),
+    ///     q: Quote::No,
+    /// }]);
+    ///
+    /// assert_eq!(lines.len(), 3);
+    /// assert_eq!(lines.get("BASIC"), Some("extended".to_string()));
+    ///
+    /// // While `merge` removes the previous definition
+    /// lines.merge(Lines::from("BASIC=merged"));
+    ///
+    /// assert_eq!(lines.len(), 2);
+    /// ```
+    pub fn extend(&mut self, pairs: impl IntoIterator<Item = KeyVal<'l>>) {
+        let pairs = pairs.into_iter().enumerate().map(|(idx, kv)| Pair {
+            kv: kv.into(),
+            append: false,
+            line: idx + 1,
+            end: idx + 1,
+        });
+
+        self.lines.extend(pairs);
+    }
+
+    /// Returns the lines with every key defined only once, in the order of the last line of each
+    /// key. Each key is a single `key=val` pair at the position of its last line, where the value
+    /// is the last `key=val` pair with the following `key+=val` pairs appended to it, same as
+    /// [`Lines::get`]. The pair keeps the quote of the last `key=val`, so [`Lines::expand`]
+    /// substitutes the joined value as a whole. So the conversions without the substitution return
+    /// the same values as for `self`, unlike the raw pairs of [`Lines::entries`] it doesn't have
+    /// the overridden definitions.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let lines = Lines::from("A=a\nB=b\nA=again\nC=c\nB+=_appended");
+    /// let effective = lines.effective();
+    ///
+    /// assert_eq!(
+    ///     effective.to_vec(),
+    ///     vec![
+    ///         ("A".to_string(), "again".to_string()),
+    ///         ("C".to_string(), "c".to_string()),
+    ///         ("B".to_string(), "b_appended".to_string())
+    ///     ]
+    /// );
+    /// assert_eq!(effective.to_btree_map(), lines.to_btree_map());
+    /// ```
+    pub fn effective(&self) -> Lines<'l> {
+        // Position of each key in `lines`, where the previous position is left empty
+        let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut lines: Vec<Option<Pair<'l>>> = Vec::with_capacity(self.lines.len());
+
+        for pair in &self.lines {
+            let prev = index
+                .insert(&pair.kv.k, lines.len())
+                .and_then(|x| lines[x].take());
+
+            let pair = match prev {
+                Some(mut prev) if pair.append => {
+                    prev.kv.v.push_str(&pair.kv.v);
+                    Pair {
+                        line: pair.line,
+                        end: pair.end,
+                        ..prev
+                    }
+                }
+                _ => Pair {
+                    append: false,
+                    ..pair.clone()
+                },
+            };
+
+            lines.push(Some(pair));
+        }
+
+        Self {
+            lines: lines.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Keeps only the pairs for which the predicate returns `true`, same as [`Vec::retain`]. The
+    /// order of the remaining pairs is preserved. Every pair is passed to the predicate as an
+    /// [`Entry`], both `key=val` and `key+=val`.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let mut lines = Lines::from("AWS_KEY=key\nBASIC=basic\nAWS_SECRET=secret\nQUOTED='quoted'");
+    /// lines.retain(|x| !x.key.starts_with("AWS_"));
+    ///
+    /// assert_eq!(
+    ///     lines.to_vec(),
+    ///     vec![
+    ///         ("BASIC".to_string(), "basic".to_string()),
+    ///         ("QUOTED".to_string(), "quoted".to_string())
+    ///     ]
+    /// );
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&Entry) -> bool) {
+        self.lines.retain(|x| f(&x.entry()));
+    }
+
+    /// Returns an iterator over every pair, in the same order as the lines, including the
+    /// duplicate keys
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{Lines, Quote};
+    ///
+    /// let lines = Lines::from("BASIC=basic\nEXPANDED=\"${BASIC}\"\nBASIC+=_appended");
+    /// let entries: Vec<_> = lines.entries().collect();
+    ///
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(entries[1].key, "EXPANDED");
+    /// assert_eq!(entries[1].raw, "${BASIC}");
+    /// assert_eq!(entries[1].quote, Quote::Double);
+    /// assert!(entries[2].append);
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = Entry<'_>> {
+        self.lines.iter().map(Pair::entry)
+    }
+
+    // Every pair as `(line, key, append)`, in the same order as `entries`
+    #[cfg(feature = "std")]
+    pub(crate) fn positions(&self) -> impl Iterator<Item = (usize, &str, bool)> {
+        self.lines
+            .iter()
+            .map(|x| (x.line, x.kv.k.as_ref(), x.append))
+    }
+
+    /// Returns the number of `key=val` pairs, including the duplicate keys
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let lines = Lines::from("# comment\nBASIC=basic\n\nBASIC=again");
+    ///
+    /// assert_eq!(lines.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns `true` if there are no `key=val` pairs
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// assert!(Lines::from("# only a comment\n").is_empty());
+    /// assert!(!Lines::from("BASIC=basic").is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the value of the key without any variable substitution, if it is defined. Same as
+    /// the rest of the conversions, the last definition wins and `key+=val` is appended to the
+    /// previous value.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let lines = Lines::from("BASIC=basic\nBASIC=again\nLIST=a\nLIST+=,b");
+    ///
+    /// assert_eq!(lines.get("BASIC"), Some("again".to_string()));
+    /// assert_eq!(lines.get("LIST"), Some("a,b".to_string()));
+    /// assert_eq!(lines.get("MISSING"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<String> {
+        Self::value_of(&self.lines, key)
+    }
+
+    /// Normalizes the case of every key. If different keys end up being the same after the
+    /// transformation then the last one wins, like any other duplicate key, and the colliding key
+    /// is returned so that it can be reported.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{KeyTransform, Lines};
+    ///
+    /// const LINES: &str = r#"
+    /// Foo=foo
+    /// bar=bar
+    /// FOO=FOO
+    /// "#;
+    ///
+    /// let mut lines = Lines::from(LINES);
+    /// let collisions = lines.transform_keys(KeyTransform::Upper);
+    /// let parsed = lines.to_btree_map();
+    ///
+    /// assert_eq!(collisions, vec!["FOO".to_string()]);
+    /// assert_eq!(parsed.get("FOO").unwrap(), &"FOO".to_string());
+    /// assert_eq!(parsed.get("BAR").unwrap(), &"bar".to_string());
+    /// ```
+    pub fn transform_keys(&mut self, transform: KeyTransform) -> Vec<String> {
+        let mut seen: BTreeMap<String, String> = BTreeMap::new();
+        let mut collisions = Vec::new();
+
+        for line in self.lines.iter_mut().map(|x| &mut x.kv) {
+            let key = match transform {
+                KeyTransform::Upper => line.k.to_uppercase(),
+                KeyTransform::Lower => line.k.to_lowercase(),
+                KeyTransform::None => return collisions,
+            };
+
+            match seen.get(&key) {
+                Some(orgnl) if orgnl != &line.k && !collisions.contains(&key) => {
+                    collisions.push(key.clone())
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(key.clone(), line.k.to_string());
+                }
+            }
+
+            if key != line.k {
+                line.k = Cow::Owned(key);
+            }
+        }
+
+        collisions
+    }
+
+    /// Converts the lines into a vec of `(key, value)` pairs, in the same order as the lines
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let parsed = Lines::from("B=b\nA=a").to_vec();
+    ///
+    /// assert_eq!(
+    ///     parsed,
+    ///     vec![
+    ///         ("B".to_string(), "b".to_string()),
+    ///         ("A".to_string(), "a".to_string())
+    ///     ]
+    /// );
+    /// ```
+    pub fn to_vec(&self) -> Vec<(String, String)> {
+        let mut vars = BTreeMap::new();
+
+        self.lines
+            .iter()
+            .map(|line| {
+                let val = line.resolve(&line.kv.v, vars.get(line.kv.k.as_ref()));
+
+                vars.insert(line.kv.k.to_string(), val.clone());
+
+                (line.kv.k.to_string(), val)
+            })
+            .collect()
+    }
+
     /// Parses the lines and converts into a hashmap
     ///
     /// Example
@@ -71,17 +1016,114 @@ impl<'l> Lines<'l> {
     /// assert_eq!(parsed.get("BASIC").unwrap(), &"basic".to_string());
     /// assert_eq!(parsed.get("QUOTED").unwrap(), &"quoted".to_string());
     /// ```
+    #[cfg(feature = "std")]
     pub fn to_hash_map(&self) -> HashMap<String, String> {
         let lines = &self.lines;
         let mut hash = HashMap::with_capacity(lines.len());
 
         for line in lines {
-            hash.insert(line.k.to_string(), line.v.to_string());
+            let val = line.resolve(&line.kv.v, hash.get(line.kv.k.as_ref()));
+
+            hash.insert(line.kv.k.to_string(), val);
         }
 
         hash
     }
 
+    /// Same as [`Lines::to_hash_map`], but the map is sorted by the key. Unlike the hashmap, this
+    /// is also available without the `std` feature.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let parsed = Lines::from("B=b\nA=a\nLIST=a\nLIST+=,b").to_btree_map();
+    /// let keys: Vec<_> = parsed.keys().collect();
+    ///
+    /// assert_eq!(keys, vec!["A", "B", "LIST"]);
+    /// assert_eq!(parsed.get("LIST").unwrap(), &"a,b".to_string());
+    /// ```
+    pub fn to_btree_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::new();
+
+        for line in &self.lines {
+            let val = line.resolve(&line.kv.v, map.get(line.kv.k.as_ref()));
+
+            map.insert(line.kv.k.to_string(), val);
+        }
+
+        map
+    }
+
+    /// Same as [`Lines::to_hash_map`], but consumes the lines so that the keys and the values are
+    /// moved into the map instead of being cloned
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let parsed = Lines::from("BASIC=basic\nLIST=a\nLIST+=,b").into_hash_map();
+    ///
+    /// assert_eq!(parsed.get("BASIC").unwrap(), &"basic".to_string());
+    /// assert_eq!(parsed.get("LIST").unwrap(), &"a,b".to_string());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn into_hash_map(self) -> HashMap<String, String> {
+        let mut hash: HashMap<String, String> = HashMap::with_capacity(self.lines.len());
+
+        for line in self.lines {
+            let Var { k, v, .. } = line.kv;
+
+            match hash.get_mut(k.as_ref()) {
+                Some(prev) if line.append => prev.push_str(&v),
+                Some(prev) => *prev = v,
+                None => {
+                    hash.insert(k.into_owned(), v);
+                }
+            }
+        }
+
+        hash
+    }
+
+    /// Collects the indexed keys i.e. `SERVERS[0]`, `SERVERS[1]` into a vec under the name of
+    /// the key, ordered by the index. The index only decides the order, the missing indexes are
+    /// skipped and not filled. Same as [`Lines::to_hash_map`], the last definition of an index
+    /// wins. Other keys are ignored.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::Lines;
+    ///
+    /// let lines = Lines::from("SERVERS[1]=b\nSERVERS[0]=a\nSERVERS[5]=c\nBASIC=basic");
+    /// let arrays = lines.to_arrays();
+    ///
+    /// assert_eq!(arrays.len(), 1);
+    /// assert_eq!(arrays.get("SERVERS").unwrap(), &vec!["a", "b", "c"]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_arrays(&self) -> HashMap<String, Vec<String>> {
+        let mut indexed: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+
+        for (key, val) in self.to_hash_map() {
+            if let Some((name, idx)) = Self::split_index(&key) {
+                indexed
+                    .entry(name.to_string())
+                    .or_default()
+                    .push((idx, val));
+            }
+        }
+
+        indexed
+            .into_iter()
+            .map(|(name, mut vals)| {
+                vals.sort_by_key(|(idx, _)| *idx);
+
+                (name, vals.into_iter().map(|(_, v)| v).collect())
+            })
+            .collect()
+    }
+
     /// Parses the lines and does variable substitution then converts into a hashmap
     ///
     /// Example
@@ -98,70 +1140,193 @@ impl<'l> Lines<'l> {
     /// assert_eq!(parsed.get("BASIC").unwrap(), &"basic".to_string());
     /// assert_eq!(parsed.get("EXPANDED").unwrap(), &"basic_is_expanded".to_string());
     /// ```
+    #[cfg(feature = "std")]
     pub fn expand(&self) -> HashMap<String, String> {
-        let mut vars = Self::to_hash_map(self);
+        self.expand_with(&ExpandOptions::default())
+    }
+
+    /// Same as [`Lines::expand`] but with the provided [`ExpandOptions`]
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{ExpandOptions, Lines};
+    ///
+    /// const LINES: &str = r#"
+    /// BASIC=basic
+    /// EXPANDED="${BASIC}_${PATH}"
+    /// "#;
+    ///
+    /// let opts = ExpandOptions {
+    ///     env_fallback: false,
+    ///     ..Default::default()
+    /// };
+    /// let parsed = Lines::from(LINES).expand_with(&opts);
+    ///
+    /// assert_eq!(parsed.get("EXPANDED").unwrap(), &"basic_".to_string());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn expand_with(&self, opts: &ExpandOptions) -> HashMap<String, String> {
+        self.expand_to_vec(opts).into_iter().collect()
+    }
+
+    /// Same as [`Lines::expand_with`] but keeps the order of the lines, including the
+    /// duplicate keys. Collecting the result into a map gives the same result as
+    /// [`Lines::expand_with`].
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{ExpandOptions, Lines};
+    ///
+    /// const LINES: &str = r#"
+    /// BASIC=basic
+    /// EXPANDED="${BASIC}_is_expanded"
+    /// "#;
+    ///
+    /// let parsed = Lines::from(LINES).expand_to_vec(&ExpandOptions::default());
+    ///
+    /// assert_eq!(
+    ///     parsed,
+    ///     vec![
+    ///         ("BASIC".to_string(), "basic".to_string()),
+    ///         ("EXPANDED".to_string(), "basic_is_expanded".to_string())
+    ///     ]
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn expand_to_vec(&self, opts: &ExpandOptions) -> Vec<(String, String)> {
+        self.expand_to_vec_in(HashMap::new(), opts)
+    }
+
+    // Same as `expand_to_vec`, but the variables which are not in the lines can also be defined
+    // somewhere else i.e. other files
+    #[cfg(feature = "std")]
+    pub(crate) fn expand_to_vec_in(
+        &self,
+        others: HashMap<String, String>,
+        opts: &ExpandOptions,
+    ) -> Vec<(String, String)> {
+        let mut all = others.clone();
+        all.extend(self.to_hash_map());
+
+        let (mut expanded, forward) = self.expand_pass(&others, all, opts);
+
+        // A reference to a key which is defined later i.e. in a later file gets the value of the
+        // previous pass, so the values are substituted again until none of those changes. Every
+        // pass resolves at least one more level of a chain, so the number of the lines is enough,
+        // and a cycle i.e. `A="${B}"` with `B="${A}"` stops there as well.
+        for _ in 0..self.lines.len() {
+            if forward.is_empty() {
+                break;
+            }
+
+            let mut all = others.clone();
+            all.extend(expanded.iter().cloned());
+
+            let (next, _) = self.expand_pass(&others, all, opts);
+
+            if next == expanded {
+                break;
+            }
+
+            expanded = next;
+        }
+
+        expanded
+    }
+
+    // Substitutes the lines in order, where `all` has the values of the keys which are not
+    // defined yet. Also returns the keys which are referenced before they are defined.
+    #[cfg(feature = "std")]
+    fn expand_pass(
+        &self,
+        others: &HashMap<String, String>,
+        all: HashMap<String, String>,
+        opts: &ExpandOptions,
+    ) -> (Vec<(String, String)>, HashSet<String>) {
+        let mut expander = Expander::new(all, opts);
+        let mut expanded = Vec::with_capacity(self.lines.len());
+
+        // Previous values of the keys, which are used by `key+=val`
+        let mut vars = HashMap::with_capacity(self.lines.len());
 
         for line in &self.lines {
-            if let KeyVal {
-                q: Quote::Double,
-                k,
-                v,
-            } = line
-            {
-                let mut new_val = String::with_capacity(v.len());
-                let mut chars = v.chars();
-
-                loop {
-                    match chars.next() {
-                        Some('$') => {
-                            let (key, is_consumed): (String, bool) = match chars.next() {
-                                Some('{') => {
-                                    (chars.by_ref().take_while(|c| c != &'}').collect(), false)
-                                }
-                                Some(x) => {
-                                    let key: String = chars
-                                        .by_ref()
-                                        .take_while(|c| c.is_alphanumeric() || c == &'_')
-                                        .collect();
-
-                                    let k = x.to_string() + &key;
-
-                                    (k, true)
-                                }
-                                _ => (String::with_capacity(0), false),
-                            };
-
-                            let found = match vars.get(&key) {
-                                Some(x) => x.to_string(),
-                                _ => env::var(&key).unwrap_or_default(),
-                            };
-
-                            new_val.push_str(&found);
-
-                            if is_consumed {
-                                // Need to find the terminator charactor
-                                // Which is also consumed by the take_while() above
-                                let idx = chars.clone().count();
-
-                                // If we reach the end of the string
-                                if idx == 0 {
-                                    continue;
-                                }
-
-                                if let Some(consumed) = v.chars().rev().skip(idx).take(1).next() {
-                                    new_val.push(consumed);
-                                };
-                            }
-                        }
-                        Some(a) => new_val.push(a),
-                        _ => break,
-                    }
+            let Var { k, v, q } = &line.kv;
+
+            // Referencing the key itself i.e. `PATH="${PATH}:/bin"` gives its previous value, or
+            // falls back to the other sources, instead of this very definition
+            if q == &Quote::Double {
+                match vars.get(k.as_ref()).or_else(|| others.get(k.as_ref())) {
+                    Some(prev) => expander.define(k, prev.clone()),
+                    None => expander.undefine(k),
                 }
+            }
 
-                vars.insert(k.to_string(), new_val);
+            let new_val = match q {
+                Quote::Double => line.resolve(&expander.expand(v), vars.get(k.as_ref())),
+                _ => line.resolve(v, vars.get(k.as_ref())),
+            };
+
+            if q == &Quote::Double || line.append {
+                expander.define(k, new_val.clone());
             }
+
+            vars.insert(k.to_string(), new_val.clone());
+            expanded.push((k.to_string(), new_val));
         }
 
-        vars
+        (expanded, expander.take_forward())
+    }
+
+    /// Returns the variables which are referenced by the substitution but are not defined in
+    /// the lines, or in the running process if [`ExpandOptions::env_fallback`] is enabled. These
+    /// are substituted with an empty string. The result is a vec of `(line, name)` in the order of
+    /// the lines, where `line` is the line number of the value. The references with a default
+    /// value i.e. `${VAR:-default}` are not counted as missing.
+    ///
+    /// Example
+    /// ```
+    /// use zenv::{ExpandOptions, Lines};
+    ///
+    /// const LINES: &str = r#"
+    /// BASIC=basic
+    /// URL="http://${HOST}:${PORT:-80}/${BASIC}"
+    /// NAME='${NOT_EXPANDED}'
+    /// "#;
+    ///
+    /// let opts = ExpandOptions {
+    ///     env_fallback: false,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let missing = Lines::from(LINES).missing(&opts);
+    ///
+    /// assert_eq!(missing, vec![(3, "HOST".to_string())]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn missing(&self, opts: &ExpandOptions) -> Vec<(usize, String)> {
+        self.missing_in(self.to_hash_map(), opts)
+    }
+
+    // Same as `missing`, but the variables can also be defined somewhere else i.e. other files
+    #[cfg(feature = "std")]
+    pub(crate) fn missing_in(
+        &self,
+        vars: HashMap<String, String>,
+        opts: &ExpandOptions,
+    ) -> Vec<(usize, String)> {
+        let expander = Expander::new(vars, opts);
+
+        self.lines
+            .iter()
+            .filter(|x| x.kv.q == Quote::Double)
+            .flat_map(|x| {
+                expander.expand(&x.kv.v);
+
+                expander
+                    .take_missing()
+                    .into_iter()
+                    .map(move |name| (x.line, name))
+            })
+            .collect()
     }
 }
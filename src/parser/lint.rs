@@ -0,0 +1,102 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{
+    line::{Line, Quote},
+    options::ParseOptions,
+};
+
+const S_QUOTE: char = '\'';
+const D_QUOTE: char = '"';
+const B_TICK: char = '`';
+
+/// A non-fatal issue found by [`lint`]
+#[derive(Debug, PartialEq)]
+pub struct Lint {
+    /// Line number, starting from 1
+    pub line: usize,
+
+    /// Content of the line
+    pub content: String,
+
+    /// What looks wrong
+    pub msg: String,
+}
+
+/// Looks for the lines which are valid but ambiguous, like an unquoted value with trailing
+/// whitespace. The lines are read with the provided [`ParseOptions`], so a `#` without a space
+/// before it is only a comment if [`ParseOptions::hash_comments_require_space`] is disabled.
+/// This doesn't depend on how the value is parsed i.e. the trailing whitespace is reported even
+/// though it is trimmed. These are reported:
+///
+/// - Trailing whitespace after an unquoted value
+/// - Opening quote without the closing quote, which is taken literally as a part of the value
+/// - Key which is already defined by a previous line, except `KEY+=value`
+///
+/// Example
+/// ```
+/// use zenv::ParseOptions;
+///
+/// let src = "BASIC=basic\nTRAILING=trailing  \nQUOTED='quoted  '";
+/// let lints = zenv::lint(src, &ParseOptions::default());
+///
+/// assert_eq!(lints.len(), 1);
+/// assert_eq!(lints[0].line, 2);
+/// assert_eq!(lints[0].content, "TRAILING=trailing  ");
+/// ```
+pub fn lint(src: &str, opts: &ParseOptions) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    // Line numbers of the keys which are already defined
+    let mut defined: BTreeMap<String, usize> = BTreeMap::new();
+
+    for (idx, line) in src.lines().enumerate() {
+        let mut push = |msg: String| {
+            lints.push(Lint {
+                line: idx + 1,
+                content: line.to_string(),
+                msg,
+            })
+        };
+
+        let (kv, append) = match Line::parse(line, opts) {
+            Line::KeyVal(kv) => (kv, false),
+            Line::Append(kv) => (kv, true),
+            Line::Empty => continue,
+        };
+
+        match defined.get(kv.k) {
+            Some(prev) if !append => {
+                push(format!("Duplicate key, already defined on line {}", prev))
+            }
+            _ => {}
+        }
+
+        defined.insert(kv.k.to_string(), idx + 1);
+
+        if kv.q != Quote::No {
+            continue;
+        }
+
+        let val = line.split_once('=').map_or("", |(_, v)| v);
+        let has_comment = Line::strip_comment(val, opts.hash_comments_require_space) != val;
+
+        if !has_comment && val.trim_end() != val {
+            push("Trailing whitespace after an unquoted value".to_string());
+        }
+
+        let mut chars = val.trim_start().chars();
+
+        if let Some(q @ (S_QUOTE | D_QUOTE | B_TICK)) = chars.next() {
+            if !chars.any(|x| x == q) {
+                push(format!("Unterminated quote, the {} is taken literally", q));
+            }
+        }
+    }
+
+    lints
+}
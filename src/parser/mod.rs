@@ -1,5 +1,17 @@
+mod error;
+#[cfg(feature = "std")]
+mod expand;
 mod line;
 mod lines;
+mod lint;
+mod options;
 
+pub use error::*;
+#[cfg(feature = "std")]
+pub use expand::substitute;
+#[cfg(feature = "std")]
+pub(crate) use expand::Expander;
 pub use line::*;
 pub use lines::*;
+pub use lint::*;
+pub use options::*;
@@ -0,0 +1,146 @@
+use alloc::{format, string::String};
+use core::str::FromStr;
+
+/// Options to control the parsing done by [`Lines::parse`](super::Lines::parse) and
+/// [`Line::parse`](super::Line::parse)
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Whether to recognize the heredoc style multiline values i.e. `KEY<<EOF` or `KEY=<<EOF`.
+    /// Every following line is taken verbatim, until a line equal to the delimiter is found.
+    /// Disabled by default.
+    pub heredoc: bool,
+
+    /// Whether `#` only starts a comment in an unquoted value if it is preceded by a whitespace,
+    /// so that values like `COLOR=#fff` or `URL=http://host/#anchor` are kept as is. When
+    /// disabled, any `#` starts a comment, which makes `KEY=#comment` an empty value.
+    /// Enabled by default.
+    pub hash_comments_require_space: bool,
+
+    /// Whether to fail on the lines which are not empty, not a comment and not a valid
+    /// `KEY=value` pair, like a missing `=`, a key which is not a valid identifier
+    /// i.e. `[A-Za-z_][A-Za-z0-9_]*` or a text after the closing quote i.e. `KEY="a" b`. When
+    /// disabled, these lines are dropped and the text after the quote is appended to the value.
+    /// Disabled by default.
+    pub strict: bool,
+
+    /// Whether the keys can have an index at the end i.e. `SERVERS[0]`, when
+    /// [`ParseOptions::strict`] is enabled. The keys are kept as they are, see
+    /// [`Lines::to_arrays`](super::Lines::to_arrays) to collect them. Disabled by default.
+    pub allow_brackets: bool,
+
+    /// Whether the keys can also have `.`, `-` and `/` after the first character i.e.
+    /// `spring.datasource.url`, when [`ParseOptions::strict`] is enabled. These are not valid
+    /// shell identifiers but can still be read by the programs. Disabled by default.
+    pub relaxed_keys: bool,
+
+    /// Whether to recognize the conditional blocks i.e. `# zenv:if KEY=value` ... `# zenv:endif`.
+    /// The lines inside a block are only used if the variable is equal to the value, where the
+    /// variable is looked up in the previous lines of the file and then in the running process,
+    /// which is skipped without the `std` feature. Blocks can be nested, and an unbalanced `if`
+    /// or `endif` fails the parsing. When disabled, these are just comments. Disabled by default.
+    pub conditionals: bool,
+
+    /// Whether an unquoted value ending with a `\` continues on the next line i.e. `LIST=a,\`
+    /// followed by `b` is `a,b`. The backslash and the line break are removed, and the next line
+    /// is taken as it is. A `\` on the last line of the file is kept literally. Disabled by default.
+    pub line_continuation: bool,
+
+    /// Whether to keep the surrounding quotes as part of the value i.e. `KEY="value"` is `"value"`,
+    /// for the programs which expect the literal quotes. The quoted part is taken verbatim, so the
+    /// escapes like `\n` are not handled, but the comment after the closing quote is still removed.
+    /// This is only meant for interop, as the value is not what a shell would read. Disabled by
+    /// default.
+    pub keep_quotes: bool,
+
+    /// Whether the key can be quoted i.e. `"weird key"=value` or `'weird key'=value`, in which
+    /// case the key is everything inside the quotes, including the spaces and any `=`. A quoted
+    /// key is not checked by [`ParseOptions::strict`]. When disabled, the quotes are part of the
+    /// key, which makes it invalid with [`ParseOptions::strict`]. Disabled by default.
+    pub allow_quoted_keys: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            heredoc: false,
+            hash_comments_require_space: true,
+            strict: false,
+            allow_brackets: false,
+            relaxed_keys: false,
+            conditionals: false,
+            line_continuation: false,
+            keep_quotes: false,
+            allow_quoted_keys: false,
+        }
+    }
+}
+
+/// Which rules the variable substitution follows, see [`ExpandOptions::compat`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Compat {
+    /// The rules of zenv
+    #[default]
+    Zenv,
+
+    /// Follow bash more closely
+    Bash,
+}
+
+impl FromStr for Compat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zenv" => Ok(Self::Zenv),
+            "bash" => Ok(Self::Bash),
+            x => Err(format!(
+                "Invalid compat `{}`, expected one of zenv or bash",
+                x
+            )),
+        }
+    }
+}
+
+/// Options to control the variable substitution done by
+/// [`Lines::expand_with`](super::Lines::expand_with)
+#[derive(Debug, Clone)]
+pub struct ExpandOptions {
+    /// Whether to look for the variable in the running process if it is not found in the file.
+    /// When disabled, a variable which is not defined in the file is substituted with an
+    /// empty string, same as a variable which is not defined anywhere. Every variable of the
+    /// running process is only read once while substituting the lines. Enabled by default.
+    pub env_fallback: bool,
+
+    /// Which rules to follow while substituting. With [`Compat::Bash`], these differences from
+    /// the default rules are corrected:
+    ///
+    /// - `${VAR-default}` only uses the default if the variable is not defined, while
+    ///   `${VAR:-default}` also uses it for an empty value. By default, both work like `:-`.
+    /// - `$VAR` only takes the ASCII letters, digits and `_` as the name, while by default any
+    ///   alphanumeric character is taken.
+    /// - `$1` is a positional parameter i.e. only the single digit is the name, which is always
+    ///   empty as there are no parameters. By default, `$1abc` is the variable `1abc`.
+    /// - `\$` is a literal `$`. By default, the backslash is kept and the variable is substituted.
+    ///
+    /// Both leave a `$` which is not followed by a name as it is, including at the end of the
+    /// value. And an unclosed `${` is kept literally, where bash would fail. Defaults to
+    /// [`Compat::Zenv`].
+    pub compat: Compat,
+
+    /// Character which starts a variable. With `$`, the variables are `${VAR}` and `$VAR`. With
+    /// any other character, the variable is enclosed by it instead i.e. `%VAR%` or `@VAR@`, to
+    /// substitute the Windows or autoconf style templates. The operations work the same inside
+    /// i.e. `%VAR:-default%`, but can't be nested, and a doubled sigil i.e. `%%` is a literal `%`.
+    /// The sigil stays as it is if it doesn't enclose a name, like in `100% sure`. Defaults to `$`.
+    pub sigil: char,
+}
+
+impl Default for ExpandOptions {
+    fn default() -> Self {
+        Self {
+            env_fallback: true,
+            compat: Compat::Zenv,
+            sigil: '$',
+        }
+    }
+}
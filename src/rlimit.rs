@@ -0,0 +1,174 @@
+use std::process::Command;
+
+// Whether the limits can be applied on this platform, otherwise they are ignored
+pub const SUPPORTED: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+));
+
+// Resource limits of the command, applied in the child right before the exec
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    // Maximum number of open files
+    pub nofile: Option<u64>,
+
+    // Maximum size of the address space in bytes
+    pub memory: Option<u64>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.nofile.is_none() && self.memory.is_none()
+    }
+
+    // Fails before spawning anything if a limit is above the current hard limit, as a failed
+    // `setrlimit` in the child is only reported as a failed spawn
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))]
+    pub fn check(self) -> Result<(), String> {
+        for (name, resource, limit) in self.limits() {
+            let Some(limit) = limit else { continue };
+
+            let hard = sys::get(resource)
+                .map_err(|e| format!("Unable to read the limit of {} - {}", name, e))?
+                .rlim_max;
+
+            if hard != libc::RLIM_INFINITY && sys::rlim(limit) > hard {
+                return Err(format!(
+                    "Unable to set {} to {}, the hard limit is {}",
+                    name, limit, hard
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    )))]
+    pub fn check(self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // Only the soft limit is set and the hard limit is kept, so the command can raise it again
+    // up to the hard limit i.e. `ulimit -S`
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))]
+    pub fn apply(self, cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        if self.is_empty() {
+            return;
+        }
+
+        // Read in the parent, so that only `setrlimit` is called in the child
+        let limits: Vec<_> = self
+            .limits()
+            .iter()
+            .filter_map(|&(_, resource, limit)| {
+                let mut rlim = sys::get(resource).ok()?;
+                rlim.rlim_cur = sys::rlim(limit?);
+
+                Some((resource, rlim))
+            })
+            .collect();
+
+        // SAFETY: Only `setrlimit` is called in the child, which is async-signal-safe
+        unsafe {
+            cmd.pre_exec(move || {
+                for (resource, rlim) in &limits {
+                    sys::set(*resource, rlim)?;
+                }
+
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    )))]
+    pub fn apply(self, _: &mut Command) {}
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))]
+    fn limits(self) -> [(&'static str, sys::Resource, Option<u64>); 2] {
+        [
+            ("--limit-nofile", libc::RLIMIT_NOFILE, self.nofile),
+            ("--limit-memory", libc::RLIMIT_AS, self.memory),
+        ]
+    }
+}
+
+// The resources and the types are the ones of the `libc` crate, as they differ between the
+// platforms
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+))]
+mod sys {
+    use std::{convert::TryFrom, io};
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    pub type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+    pub type Resource = libc::c_int;
+
+    // Anything larger is the same as no limit
+    pub fn rlim(limit: u64) -> libc::rlim_t {
+        libc::rlim_t::try_from(limit).unwrap_or(libc::RLIM_INFINITY)
+    }
+
+    pub fn get(resource: Resource) -> io::Result<libc::rlimit> {
+        let mut rlim = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+
+        // SAFETY: `rlim` is a valid pointer for the duration of the call
+        if unsafe { libc::getrlimit(resource, &mut rlim) } == 0 {
+            Ok(rlim)
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn set(resource: Resource, rlim: &libc::rlimit) -> io::Result<()> {
+        // SAFETY: `rlim` is a valid pointer for the duration of the call
+        if unsafe { libc::setrlimit(resource, rlim) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
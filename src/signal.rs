@@ -0,0 +1,131 @@
+use std::process::Child;
+
+// Forwards `SIGTERM` and `SIGHUP` to the running commands while it is alive, so that stopping
+// `zenv` i.e. with `kill` or by closing the terminal stops all of them instead of leaving them
+// behind. `SIGINT` is not forwarded, as `Ctrl+C` already reaches the whole process group.
+#[cfg(unix)]
+pub struct Forward {
+    previous: Vec<(libc::c_int, libc::sigaction)>,
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::{
+        ptr,
+        sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, Ordering},
+    };
+
+    pub const SIGNALS: [libc::c_int; 2] = [libc::SIGTERM, libc::SIGHUP];
+
+    // Process ids of the running commands, `0` once the command is waited for as the id can be
+    // reused by then
+    pub static PIDS: AtomicPtr<Vec<AtomicI32>> = AtomicPtr::new(ptr::null_mut());
+
+    // Whether any signal is forwarded, so that the commands are not retried
+    pub static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+    pub extern "C" fn forward(signal: libc::c_int) {
+        RECEIVED.store(true, Ordering::SeqCst);
+
+        // SAFETY: The list is only freed once the handler is removed, see `Forward::drop`, and
+        // `kill` is async-signal-safe
+        if let Some(pids) = unsafe { PIDS.load(Ordering::SeqCst).as_ref() } {
+            for pid in pids.iter().map(|x| x.load(Ordering::SeqCst)) {
+                if pid > 0 {
+                    unsafe { libc::kill(pid, signal) };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Forward {
+    pub fn new(children: &[Child]) -> Self {
+        use std::{
+            mem,
+            sync::atomic::{AtomicI32, Ordering},
+        };
+
+        // Set before the handler, and freed after it is removed
+        let pids: Vec<_> = children
+            .iter()
+            .map(|x| AtomicI32::new(x.id() as i32))
+            .collect();
+        sys::PIDS.store(Box::into_raw(Box::new(pids)), Ordering::SeqCst);
+
+        let previous = sys::SIGNALS
+            .iter()
+            .filter_map(|&signal| {
+                // SAFETY: A zeroed `sigaction` is valid, and only the handler is set
+                unsafe {
+                    let mut action: libc::sigaction = mem::zeroed();
+                    let mut previous: libc::sigaction = mem::zeroed();
+
+                    action.sa_sigaction = sys::forward as extern "C" fn(libc::c_int) as usize;
+                    action.sa_flags = libc::SA_RESTART;
+                    libc::sigemptyset(&mut action.sa_mask);
+
+                    (libc::sigaction(signal, &action, &mut previous) == 0)
+                        .then_some((signal, previous))
+                }
+            })
+            .collect();
+
+        Self { previous }
+    }
+
+    // Stops forwarding to the command at the index, once it is waited for
+    pub fn exited(&self, idx: usize) {
+        use std::sync::atomic::Ordering;
+
+        // SAFETY: Only freed when dropped
+        if let Some(pid) =
+            unsafe { sys::PIDS.load(Ordering::SeqCst).as_ref() }.and_then(|x| x.get(idx))
+        {
+            pid.store(0, Ordering::SeqCst);
+        }
+    }
+
+    // Whether `SIGTERM` or `SIGHUP` is forwarded to the commands
+    pub fn received() -> bool {
+        sys::RECEIVED.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Forward {
+    fn drop(&mut self) {
+        use std::{ptr, sync::atomic::Ordering};
+
+        for (signal, previous) in &self.previous {
+            // SAFETY: Restoring the handler which is returned by `sigaction`
+            unsafe { libc::sigaction(*signal, previous, ptr::null_mut()) };
+        }
+
+        // `zenv` is single threaded while the commands run, so the handler can't be running here
+        let pids = sys::PIDS.swap(ptr::null_mut(), Ordering::SeqCst);
+
+        if !pids.is_null() {
+            // SAFETY: Created by `Box::into_raw` in `Forward::new`
+            drop(unsafe { Box::from_raw(pids) });
+        }
+    }
+}
+
+// There are no such signals on windows, and a console close already reaches the commands
+#[cfg(not(unix))]
+pub struct Forward;
+
+#[cfg(not(unix))]
+impl Forward {
+    pub fn new(_: &[Child]) -> Self {
+        Self
+    }
+
+    pub fn exited(&self, _: usize) {}
+
+    pub fn received() -> bool {
+        false
+    }
+}
@@ -0,0 +1,41 @@
+// Variables of the `[env]` table of a TOML file, in the order of the file. The values are taken
+// literally, and the rest of the file is ignored.
+#[cfg(feature = "toml")]
+pub fn read(path: &str) -> Result<Vec<(String, String)>, String> {
+    use ::toml::{Table, Value};
+
+    let src = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    let mut table: Table = src
+        .parse()
+        .map_err(|e: ::toml::de::Error| format!("{}: {}", path, e.message()))?;
+
+    let env = match table.remove("env") {
+        Some(Value::Table(env)) => env,
+        Some(x) => {
+            return Err(format!(
+                "{}: [env] is not a table, but {}",
+                path,
+                x.type_str()
+            ))
+        }
+        None => return Ok(Vec::new()),
+    };
+
+    env.into_iter()
+        .map(|(k, v)| match v {
+            Value::String(v) => Ok((k, v)),
+            x => Err(format!(
+                "{}: `{}` in [env] is not a string, but {}",
+                path,
+                k,
+                x.type_str()
+            )),
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "toml"))]
+pub fn read(_: &str) -> Result<Vec<(String, String)>, String> {
+    Err("--toml requires the `toml` feature".to_string())
+}
@@ -0,0 +1,40 @@
+use std::{fmt, path::PathBuf};
+
+/// What a [`Warning`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A line which is not a `KEY=value` pair is dropped, as
+    /// [`ParseOptions::strict`](crate::ParseOptions::strict) is disabled
+    Dropped,
+
+    /// The key is already defined earlier in the same file, so that value is never used
+    Duplicate,
+
+    /// The key is already defined by an earlier file, whose value is replaced
+    Override,
+}
+
+/// A non-fatal issue found while loading the files, see
+/// [`Zenv::parse_with_warnings`](crate::Zenv::parse_with_warnings)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Warning {
+    /// What the warning is about
+    pub kind: WarningKind,
+
+    /// File of the line
+    pub path: PathBuf,
+
+    /// Line number, starting from 1
+    pub line: usize,
+
+    /// What happened
+    pub msg: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.msg)
+    }
+}
@@ -0,0 +1,1712 @@
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+fn zenv(args: &[&str]) -> Output {
+    zenv_with(args, &[])
+}
+
+fn zenv_with(args: &[&str], envs: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_zenv"))
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    let out = zenv(args);
+
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn cli_print() {
+    let out = stdout(&["-f", "tests/.env.print", "--print"]);
+
+    assert_eq!(
+        out,
+        "BASIC=basic\nSPACED='spaced value'\nMULTILINE=\"multi\\nline\"\nQUOTE=\"it's\"\n"
+    )
+}
+
+#[test]
+fn cli_print_round_trip() {
+    let values = [
+        "MIXED=it's \"x\"",
+        "NEWLINE=it's\nmulti",
+        "SLASH=C:\\dir\\n it's",
+        "TICK=`it's`\n",
+    ];
+
+    let mut args = vec!["-f", "tests/.env.print"];
+    for x in values.iter() {
+        args.extend(["--set", x]);
+    }
+
+    let file = std::env::temp_dir().join(format!("zenv-{}.env.round", std::process::id()));
+    let file = file.to_str().unwrap();
+
+    std::fs::write(file, stdout(&[args.as_slice(), &["--print"]].concat())).unwrap();
+
+    // Formatted and then parsed, gives the same values
+    assert_eq!(
+        stdout(&["-f", file, "--json"]),
+        stdout(&[args.as_slice(), &["--json"]].concat())
+    );
+
+    std::fs::remove_file(file).unwrap();
+
+    // All the quotes can't be in the same value, as there is no escape for `"`
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--set",
+        "BAD=`it's` \"x\"",
+        "--print",
+    ]);
+
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Unable to print `BAD`, the value can't be represented in a .env file\n"
+    );
+}
+
+#[test]
+fn cli_print_escape_newlines() {
+    let out = stdout(&["-f", "tests/.env.print", "--print", "--escape-newlines"]);
+
+    assert!(out.contains("MULTILINE=\"multi\\nline\"\n"));
+}
+
+#[test]
+fn cli_export() {
+    let out = stdout(&["-f", "tests/.env.print", "--export"]);
+
+    assert!(out.contains("export MULTILINE='multi\nline'\n"));
+    assert!(out.contains("export QUOTE='it'\\''s'\n"));
+
+    let out = stdout(&["-f", "tests/.env.print", "--export", "--escape-newlines"]);
+
+    assert!(out.contains("export MULTILINE=$'multi\\nline'\n"));
+    assert!(out.contains("export QUOTE='it'\\''s'\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_export_eval() {
+    let file = std::env::temp_dir().join(format!("zenv-eval-{}.env", std::process::id()));
+
+    std::fs::write(
+        &file,
+        "A=\"multi\\nline\"\nB='back\\nslash'\nC=\"it's\\n\\\\n\"\n",
+    )
+    .unwrap();
+
+    let script = format!(
+        "eval \"$('{}' -f '{}' --export --escape-newlines)\"; printf '%s|' \"$A\" \"$B\" \"$C\"",
+        env!("CARGO_BIN_EXE_zenv"),
+        file.display()
+    );
+
+    let out = Command::new("bash").args(["-c", &script]).output().unwrap();
+
+    std::fs::remove_file(&file).ok();
+
+    assert!(out.status.success());
+    assert_eq!(out.stdout, b"multi\nline|back\\nslash|it's\n\\n|");
+}
+
+#[test]
+fn cli_print_shell() {
+    let out = stdout(&["-f", "tests/.env.print", "--print-shell", "bash"]);
+
+    assert_eq!(out, stdout(&["-f", "tests/.env.print", "--export"]));
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.print",
+        "--set",
+        "WIN=C:\\dir",
+        "--print-shell",
+        "fish",
+    ]);
+
+    assert_eq!(
+        out,
+        "set -gx BASIC 'basic'\nset -gx SPACED 'spaced value'\nset -gx MULTILINE 'multi\nline'\nset -gx QUOTE 'it\\'s'\nset -gx WIN 'C:\\\\dir'\n"
+    );
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.print",
+        "--only",
+        "MULTILINE",
+        "--print-shell",
+        "fish",
+        "--escape-newlines",
+    ]);
+
+    assert_eq!(out, "set -gx MULTILINE 'multi'\\n'line'\n");
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.print",
+        "--set",
+        "spring.url=$HOME",
+        "--print-shell",
+        "powershell",
+        "--escape-newlines",
+    ]);
+
+    assert_eq!(
+        out,
+        "$env:BASIC = 'basic'\n$env:SPACED = 'spaced value'\n$env:MULTILINE = \"multi`nline\"\n$env:QUOTE = 'it''s'\n${env:spring.url} = '$HOME'\n"
+    );
+
+    let out = zenv(&["-f", "tests/.env.print", "--print-shell", "zsh"]);
+
+    assert_eq!(out.status.code(), Some(2));
+
+    let out = zenv(&["-f", "tests/.env.print", "--print-shell", "fish", "--json"]);
+
+    assert!(!out.status.success());
+}
+
+#[test]
+fn cli_print_header() {
+    let out = stdout(&["-f", "tests/.env.header", "--print", "--print-header"]);
+
+    assert_eq!(
+        out,
+        "# Settings of the app, generated by the deploy\n# Owner: platform team\n\nHOST=localhost\nPORT=80\n"
+    );
+
+    // Only from the first file, and not with json
+    let out = stdout(&[
+        "-f",
+        "tests/.env.base",
+        "-f",
+        "tests/.env.header",
+        "--only",
+        "HOST",
+        "--export",
+        "--print-header",
+    ]);
+
+    assert_eq!(out, "export HOST='localhost'\n");
+
+    let out = stdout(&["-f", "tests/.env.header", "--json", "--print-header"]);
+
+    assert!(out.starts_with('{'));
+}
+
+#[test]
+fn cli_json() {
+    let out = stdout(&["-f", "tests/.env.basic", "--json"]);
+
+    assert_eq!(
+        out,
+        "{\n  \"BASIC\": \"basic\",\n  \"EMPTY\": \"\",\n  \"SINGLE_QUOTES\": \"single_quotes\",\n  \"DOUBLE_QUOTES\": \"double_quotes\"\n}\n"
+    )
+}
+
+#[test]
+fn cli_conflicting_formats() {
+    let out = zenv(&["-f", "tests/.env.basic", "--json", "--print"]);
+
+    assert!(!out.status.success());
+}
+
+#[test]
+fn cli_require() {
+    let out = zenv(&["-f", "tests/.env.basic", "--print", "--require", "BASIC"]);
+
+    assert!(out.status.success());
+
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--print",
+        "--require",
+        "EMPTY",
+        "--require",
+        "MISSING",
+    ]);
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Missing required variables - EMPTY, MISSING\n"
+    );
+}
+
+#[test]
+fn cli_strict_by_default() {
+    let out = zenv(&["-f", "tests/.env.invalid", "--print"]);
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: tests/.env.invalid: Line 4: Expected `KEY=value` - `NOT_A_PAIR`\n"
+    );
+
+    let out = stdout(&["-f", "tests/.env.invalid", "--print", "--lenient"]);
+
+    assert_eq!(out, "BASIC=basic\nAFTER=after\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_inherit() {
+    let out = zenv_with(
+        &[
+            "-f",
+            "tests/.env.basic",
+            "--inherit",
+            "PATH",
+            "--inherit",
+            "ZENV_INHERITED",
+            "--inherit",
+            "BASIC",
+            "--",
+            "env",
+        ],
+        &[
+            ("ZENV_INHERITED", "inherited"),
+            ("ZENV_DROPPED", "dropped"),
+            ("BASIC", "from_parent"),
+        ],
+    );
+
+    let out = String::from_utf8(out.stdout).unwrap();
+    let mut vars: Vec<&str> = out.lines().filter(|x| !x.starts_with("PATH=")).collect();
+    vars.sort_unstable();
+
+    assert_eq!(
+        vars,
+        vec![
+            "BASIC=basic",
+            "DOUBLE_QUOTES=double_quotes",
+            "EMPTY=",
+            "SINGLE_QUOTES=single_quotes",
+            "ZENV_INHERITED=inherited"
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_pid_file() {
+    let pid_file = std::env::temp_dir().join(format!("zenv-{}.pid", std::process::id()));
+    let pid_file = pid_file.to_str().unwrap();
+
+    // The file is written after spawning, so the command needs to wait for it
+    let script = format!(
+        "while [ ! -s {0} ]; do sleep 0.01; done; cat {0} && echo \" $$\"",
+        pid_file
+    );
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--pid-file",
+        pid_file,
+        "--",
+        "sh",
+        "-c",
+        &script,
+    ]);
+
+    let pids: Vec<&str> = out.split_whitespace().collect();
+
+    assert_eq!(pids[0], pids[1]);
+    assert!(!std::path::Path::new(pid_file).exists());
+
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--pid-file",
+        pid_file,
+        "--",
+        "zenv-does-not-exist",
+    ]);
+
+    assert!(!out.status.success());
+    assert!(!std::path::Path::new(pid_file).exists());
+}
+
+#[test]
+fn cli_multiple_files() {
+    let out = stdout(&["-f", "tests/.env.base", "-f", "tests/.env.local", "--print"]);
+
+    assert!(out.contains("HOST=localhost\n"));
+    assert!(out.contains("PORT=80\n"));
+    assert!(out.contains("DIRS=/usr/bin:/extra/bin\n"));
+}
+
+#[test]
+fn cli_print_last_definition() {
+    let out = stdout(&["-f", "tests/.env.base", "-f", "tests/.env.local", "--print"]);
+
+    assert_eq!(
+        out,
+        "PORT=80\nHOST=localhost\nDIRS=/usr/bin:/extra/bin\nFLAGS=--verbose\nURL='http://${HOST}:${PORT}'\n"
+    );
+}
+
+#[test]
+fn cli_set() {
+    let file = std::env::temp_dir().join(format!("zenv-set-{}.env", std::process::id()));
+    let path = file.to_str().unwrap();
+
+    std::fs::write(
+        &file,
+        "# Server\nHOST=example.com\nNAME='old'\n\nPORT=80 # http\nQUOTED=\"old\"\n",
+    )
+    .unwrap();
+
+    stdout(&["set", "-f", path, "NAME=new value"]);
+    stdout(&["set", "-f", path, "PORT=8080"]);
+    stdout(&["set", "-f", path, "QUOTED=it's"]);
+    stdout(&["set", "-f", path, "URL=http://localhost:8080"]);
+    stdout(&["set", "-f", path, "GREETING=hello world"]);
+
+    let out = zenv(&["set", "-f", path, "BOTH='\"`"]);
+
+    assert!(!out.status.success());
+
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "# Server\nHOST=example.com\nNAME='new value'\n\nPORT=8080 # http\nQUOTED=\"it's\"\nURL=http://localhost:8080\nGREETING='hello world'\n"
+    );
+
+    std::fs::remove_file(&file).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_set_keeps_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file = std::env::temp_dir().join(format!("zenv-set-keep-{}.env", std::process::id()));
+    let path = file.to_str().unwrap();
+
+    std::fs::write(&file, "HOST=example.com\r\nPORT=80\r\n").unwrap();
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    stdout(&["set", "-f", path, "PORT=8080"]);
+    stdout(&["comment-out", "-f", path, "HOST"]);
+
+    // The secrets stay private, and the line endings are not changed
+    let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "# HOST=example.com\r\nPORT=8080\r\n"
+    );
+
+    std::fs::remove_file(&file).ok();
+}
+
+#[test]
+fn cli_get() {
+    assert_eq!(
+        stdout(&["get", "-f", "tests/.env.basic", "BASIC"]),
+        "basic\n"
+    );
+    assert_eq!(stdout(&["get", "-f", "tests/.env.basic", "EMPTY"]), "\n");
+
+    let out = stdout(&["get", "-f", "tests/.env.expanded", "EXPANDED"]);
+    assert_eq!(out, "$BASIC-expanded\n");
+
+    let out = stdout(&["get", "-f", "tests/.env.expanded", "-x", "EXPANDED"]);
+    assert_eq!(out, "basic-expanded\n");
+
+    let out = zenv(&["get", "-f", "tests/.env.basic", "MISSING"]);
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Variable is not defined - MISSING\n"
+    );
+}
+
+#[test]
+fn cli_set_var() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.base",
+        "-f",
+        "tests/.env.local",
+        "-x",
+        "--set",
+        "HOST=from_set",
+        "--set",
+        "LITERAL=$HOST",
+        "--print",
+    ]);
+
+    assert!(out.contains("URL=http://from_set:80\n"));
+    assert!(out.contains("LITERAL='$HOST'\n"));
+    assert!(!out.contains("localhost"));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_verbose() {
+    let out = zenv(&[
+        "-f",
+        "tests/.env.base",
+        "-f",
+        "tests/.env.local",
+        "-x",
+        "--verbose",
+        "--",
+        "echo",
+        "hello",
+    ]);
+
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "hello\n");
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "zenv: tests/.env.local:1: `HOST` overrides the value of tests/.env.base:1\n\
+         zenv: loaded 5 vars from tests/.env.base, tests/.env.local (1 expanded), running: echo hello\n"
+    );
+}
+
+#[test]
+fn cli_print_effective() {
+    let out = zenv_with(
+        &[
+            "-f",
+            "tests/.env.basic",
+            "--inherit",
+            "ZENV_INHERITED",
+            "--inherit",
+            "BASIC",
+            "--print-effective",
+        ],
+        &[("ZENV_INHERITED", "inherited"), ("BASIC", "from_parent")],
+    );
+
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "ZENV_INHERITED=inherited\nBASIC=basic\nEMPTY=''\nSINGLE_QUOTES=single_quotes\nDOUBLE_QUOTES=double_quotes\n"
+    );
+
+    // Everything is inherited without --inherit
+    let out = zenv_with(
+        &["-f", "tests/.env.basic", "--print-effective", "--json"],
+        &[("ZENV_INHERITED", "inherited")],
+    );
+    let out = String::from_utf8(out.stdout).unwrap();
+
+    assert!(out.contains("\"ZENV_INHERITED\": \"inherited\""));
+    assert!(out.contains("\"BASIC\": \"basic\""));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_signal_exit_code() {
+    let out = zenv(&["-f", "tests/.env.basic", "--", "sh", "-c", "kill -9 $$"]);
+
+    assert_eq!(out.status.code(), Some(128 + 9));
+
+    let out = zenv(&["-f", "tests/.env.basic", "--", "sh", "-c", "exit 3"]);
+
+    assert_eq!(out.status.code(), Some(3));
+}
+
+#[test]
+fn cli_failure_exit_code() {
+    let code = |args: &[&str]| zenv(args).status.code();
+
+    assert_eq!(code(&["--", "echo"]), Some(2));
+    assert_eq!(
+        code(&["-f", "tests/.env.basic", "--print", "--json"]),
+        Some(2)
+    );
+    assert_eq!(code(&["-f", "tests/.env.missing", "--", "echo"]), Some(3));
+    assert_eq!(code(&["-f", "tests/.env.invalid", "--", "echo"]), Some(3));
+    assert_eq!(
+        code(&[
+            "-f",
+            "tests/.env.basic",
+            "--require",
+            "MISSING",
+            "--",
+            "echo"
+        ]),
+        Some(1)
+    );
+    assert_eq!(
+        code(&["-f", "tests/.env.basic", "--", "zenv-not-found"]),
+        Some(127)
+    );
+
+    // Found, but not executable
+    #[cfg(unix)]
+    assert_eq!(
+        code(&["-f", "tests/.env.basic", "--", "tests/.env.basic"]),
+        Some(126)
+    );
+}
+
+#[test]
+fn cli_sort() {
+    let out = stdout(&["-f", "tests/.env.basic", "--print", "--sort"]);
+
+    assert_eq!(
+        out,
+        "BASIC=basic\nDOUBLE_QUOTES=double_quotes\nEMPTY=''\nSINGLE_QUOTES=single_quotes\n"
+    );
+}
+
+#[test]
+fn cli_comment_out() {
+    let file = std::env::temp_dir().join(format!("zenv-comment-{}.env", std::process::id()));
+    let path = file.to_str().unwrap();
+
+    std::fs::write(&file, "# Flags\nLEGACY=1\nOTHER=other\nLEGACY+=,2\n").unwrap();
+
+    stdout(&["comment-out", "-f", path, "LEGACY"]);
+
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "# Flags\n# LEGACY=1\nOTHER=other\n# LEGACY+=,2\n"
+    );
+
+    stdout(&["uncomment", "-f", path, "LEGACY"]);
+
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "# Flags\nLEGACY=1\nOTHER=other\nLEGACY+=,2\n"
+    );
+
+    // Every line of a multi-line value, and the key as it is written
+    let src = "CERT<<EOF\n  line one\nline two\nEOF\nLIST=a,\\\n  b\n\"odd key\"=1\nOTHER=other\n";
+    let opts = ["--heredoc", "--line-continuation", "--allow-quoted-keys"];
+
+    std::fs::write(&file, src).unwrap();
+
+    for key in ["CERT", "LIST", "odd key"].iter() {
+        stdout(&[&["comment-out", "-f", path, key], &opts[..]].concat());
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "# CERT<<EOF\n#   line one\n# line two\n# EOF\n# LIST=a,\\\n#   b\n# \"odd key\"=1\nOTHER=other\n"
+    );
+
+    let out = stdout(&[&["-f", path, "--print"], &opts[..]].concat());
+
+    assert_eq!(out, "OTHER=other\n");
+
+    for key in ["CERT", "LIST", "odd key"].iter() {
+        stdout(&[&["uncomment", "-f", path, key], &opts[..]].concat());
+    }
+
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), src);
+
+    let out = zenv(&["comment-out", "-f", path, "MISSING"]);
+
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        format!(
+            "zenv: Nothing to change, `MISSING` is not found in {}\n",
+            path
+        )
+    );
+
+    std::fs::remove_file(&file).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_without_dash_dash() {
+    let out = stdout(&["-f", "tests/.env.basic", "sh", "-c", "echo $BASIC"]);
+
+    assert_eq!(out, "basic\n");
+
+    // Everything after the command belongs to the command
+    let out = stdout(&["-f", "tests/.env.basic", "echo", "-x", "--print"]);
+
+    assert_eq!(out, "-x --print\n");
+
+    let out = stdout(&["-f", "tests/.env.basic", "--", "echo", "-x"]);
+
+    assert_eq!(out, "-x\n");
+
+    // A `--` after the command is passed to it, i.e. `npm run dev -- --port 3000`
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "echo",
+        "hi",
+        "--",
+        "--port",
+        "3000",
+    ]);
+
+    assert_eq!(out, "hi -- --port 3000\n");
+
+    let out = stdout(&["-f", "tests/.env.basic", "--", "echo", "--", "-x"]);
+
+    assert_eq!(out, "-- -x\n");
+}
+
+#[test]
+fn cli_format() {
+    let out = stdout(&["-f", "tests/.env.print", "--format", "dotenv"]);
+
+    assert_eq!(out, stdout(&["-f", "tests/.env.print", "--print"]));
+
+    let out = stdout(&["-f", "tests/.env.print", "--format", "yaml"]);
+
+    assert_eq!(
+        out,
+        "BASIC: \"basic\"\nSPACED: \"spaced value\"\nMULTILINE: \"multi\\nline\"\nQUOTE: \"it's\"\n"
+    );
+
+    // The keys which a YAML loader reads as something else than a string are quoted
+    let path = std::env::temp_dir().join(format!("zenv-yaml-{}.env", std::process::id()));
+
+    std::fs::write(&path, "TRUE=1\nY=2\nnull=3\nOn=4\n_123=5\n").unwrap();
+
+    let out = stdout(&["-f", path.to_str().unwrap(), "--format", "yaml"]);
+
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(
+        out,
+        "\"TRUE\": \"1\"\n\"Y\": \"2\"\n\"null\": \"3\"\n\"On\": \"4\"\n_123: \"5\"\n"
+    );
+
+    let out = stdout(&["-f", "tests/.env.print", "--docker-args"]);
+
+    assert_eq!(
+        out,
+        "-e 'BASIC=basic' -e 'SPACED=spaced value' -e 'MULTILINE=multi\nline' -e 'QUOTE=it'\\''s'\n"
+    );
+
+    let out = zenv(&["-f", "tests/.env.print", "--format", "json", "--export"]);
+
+    assert!(!out.status.success());
+
+    let out = zenv(&["-f", "tests/.env.print", "--format", "toml"]);
+
+    assert!(!out.status.success());
+}
+
+#[test]
+fn cli_template() {
+    let args = [
+        "-f",
+        "tests/.env.base",
+        "-f",
+        "tests/.env.local",
+        "--template",
+        "tests/template.conf",
+    ];
+
+    let expected = "server {\n    listen 80;\n    server_name localhost;\n    #  is empty\n}\n";
+
+    assert_eq!(stdout(&args), expected);
+
+    let file = std::env::temp_dir().join(format!("zenv-template-{}.conf", std::process::id()));
+    let path = file.to_str().unwrap();
+
+    let out = stdout(&[&args[..], &["--out", path]].concat());
+
+    assert_eq!(out, "");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), expected);
+
+    std::fs::remove_file(&file).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_expand_args() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--expand-args",
+        "--",
+        "echo",
+        "${BASIC}-arg",
+        "$SINGLE_QUOTES",
+    ]);
+
+    assert_eq!(out, "basic-arg single_quotes\n");
+
+    let out = stdout(&["-f", "tests/.env.basic", "--", "echo", "${BASIC}"]);
+
+    assert_eq!(out, "${BASIC}\n");
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--expand-args",
+        "--expand-sigil",
+        "%",
+        "--",
+        "echo",
+        "%BASIC%-arg",
+        "${BASIC}",
+    ]);
+
+    assert_eq!(out, "basic-arg ${BASIC}\n");
+
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--expand-sigil",
+        "x",
+        "--",
+        "echo",
+    ]);
+
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_parallel() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--parallel",
+        "sh -c 'echo first $BASIC'",
+        "sh -c \"sleep 0.1 && echo 'second  spaced'\"",
+    ]);
+
+    assert_eq!(out, "first basic\nsecond  spaced\n");
+
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--parallel",
+        "--",
+        "true",
+        "sh -c 'exit 3'",
+        "sh -c 'exit 4'",
+    ]);
+
+    assert_eq!(out.status.code(), Some(3));
+
+    let out = zenv(&["-f", "tests/.env.basic", "--parallel", "echo 'unclosed"]);
+
+    assert!(!out.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_parallel_forwards_signals() {
+    for (signal, code) in [("TERM", 143), ("HUP", 129)] {
+        let pid_file = std::env::temp_dir().join(format!(
+            "zenv-forward-{}-{}.pid",
+            signal,
+            std::process::id()
+        ));
+
+        let mut child = Command::new(env!("CARGO_BIN_EXE_zenv"))
+            .args(["-f", "tests/.env.basic", "--pid-file"])
+            .arg(&pid_file)
+            .args(["--parallel", "sleep 30", "sleep 30"])
+            .spawn()
+            .unwrap();
+
+        // The file is written after spawning every command
+        while std::fs::read_to_string(&pid_file).map_or(true, |x| x.lines().count() < 2) {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let pids = std::fs::read_to_string(&pid_file).unwrap();
+        let start = std::time::Instant::now();
+
+        Command::new("kill")
+            .args([format!("-{}", signal), child.id().to_string()])
+            .status()
+            .unwrap();
+
+        let status = child.wait().unwrap();
+
+        assert_eq!(status.code(), Some(code));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+        // Both of them are waited for, so they are gone
+        for pid in pids.lines() {
+            let alive = Command::new("kill").args(["-0", pid]).output().unwrap();
+
+            assert!(!alive.status.success());
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_require_nonempty() {
+    let out = stdout(&["-f", "tests/.env.empty", "--", "echo", "ran"]);
+
+    assert_eq!(out, "ran\n");
+
+    let out = zenv(&["-f", "tests/.env.empty", "--require-nonempty", "--", "echo"]);
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: No variables found in tests/.env.empty\n"
+    );
+
+    assert!(
+        zenv(&["-f", "tests/.env.basic", "--require-nonempty", "--print"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn cli_print_missing() {
+    let path = std::env::temp_dir().join(format!("zenv-missing-{}.env", std::process::id()));
+    let file = path.to_str().unwrap();
+
+    std::fs::write(
+        &path,
+        "HOST=localhost\nURL=\"http://${HOST}:${ZENV_NO_PORT}\"\n\nNAME='${NOT_EXPANDED}'\nDSN=\"$ZENV_NO_USER@${HOST:-x}\"\n",
+    )
+    .unwrap();
+
+    let out = stdout(&["-f", file, "--print-missing"]);
+
+    assert_eq!(
+        out,
+        format!("{0}:2: ZENV_NO_PORT\n{0}:5: ZENV_NO_USER\n", file)
+    );
+
+    // Only listed, even with --strict, and the process is not looked up without the fallback
+    let env = [("ZENV_NO_PORT", "80")];
+    let args = ["-f", file, "--print-missing", "--strict"];
+    let out = zenv_with(&args, &env);
+
+    assert!(out.status.success());
+    assert_eq!(out.stdout, format!("{}:5: ZENV_NO_USER\n", file).as_bytes());
+
+    let out = zenv_with(&[&args[..], &["--no-env-fallback"]].concat(), &env);
+
+    std::fs::remove_file(&path).ok();
+
+    assert!(out.status.success());
+    assert_eq!(
+        out.stdout,
+        format!("{0}:2: ZENV_NO_PORT\n{0}:5: ZENV_NO_USER\n", file).as_bytes()
+    );
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn cli_url() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    // Serves the file and then a missing one
+    let server = std::thread::spawn(move || {
+        let responses = [
+            "HTTP/1.0 200 OK\r\n\r\nHOST=remote\nREMOTE=yes\n",
+            "HTTP/1.0 404 Not Found\r\n\r\nnot found",
+        ];
+
+        for res in responses.iter() {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut req = Vec::new();
+            let mut buf = [0; 1024];
+
+            while !req.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                req.extend_from_slice(&buf[..n]);
+            }
+
+            stream.write_all(res.as_bytes()).unwrap();
+        }
+    });
+
+    let app = format!("{}/app.env", url);
+    let out = stdout(&["-f", "tests/.env.base", "--url", &app, "--print"]);
+
+    assert_eq!(out, "PORT=80\nDIRS=/usr/bin\nHOST=remote\nREMOTE=yes\n");
+
+    let missing = format!("{}/missing.env", url);
+    let out = zenv(&["--url", &missing, "--", "echo", "ran"]);
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        format!("ERROR :: Unable to fetch {} - Status 404\n", missing)
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+fn cli_only_and_unset() {
+    let out = stdout(&["-f", "tests/.env.base", "--only", "*S*", "--print"]);
+
+    assert_eq!(out, "HOST=example.com\nDIRS=/usr/bin\n");
+
+    let out = stdout(&["-f", "tests/.env.base", "--only", "?O??", "--print"]);
+
+    assert_eq!(out, "HOST=example.com\nPORT=80\n");
+
+    let out = stdout(&["-f", "tests/.env.base", "--unset", "*O*", "--print"]);
+
+    assert_eq!(out, "DIRS=/usr/bin\n");
+
+    let out = zenv(&[
+        "-f",
+        "tests/.env.base",
+        "--only",
+        "NONE_*",
+        "--verbose",
+        "--print",
+    ]);
+
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "");
+    assert!(String::from_utf8(out.stderr)
+        .unwrap()
+        .contains("`NONE_*` doesn't match any variable"));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_unset_inherited() {
+    let out = zenv_with(
+        &[
+            "-f",
+            "tests/.env.basic",
+            "--unset",
+            "ZENV_UNSET_*",
+            "--",
+            "sh",
+            "-c",
+            "echo \"${ZENV_UNSET_A:-unset}\"",
+        ],
+        &[("ZENV_UNSET_A", "inherited")],
+    );
+
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "unset\n");
+}
+
+#[test]
+fn cli_interpolate_from() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.local",
+        "--interpolate-from",
+        "tests/.env.base",
+        "-x",
+        "--only",
+        "URL",
+        "--print",
+    ]);
+
+    assert_eq!(out, "URL=http://localhost:80\n");
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.local",
+        "--interpolate-from",
+        "tests/.env.base",
+        "-x",
+        "--json",
+    ]);
+
+    assert!(!out.contains("PORT"));
+}
+
+#[test]
+fn cli_max_size() {
+    let run = |opt: &str, max: &str| zenv(&["-f", "tests/.env.basic", opt, max, "--", "echo"]);
+
+    assert!(run("--max-var-size", "13").status.success());
+    assert!(run("--max-env-size", "71").status.success());
+
+    let out = run("--max-var-size", "12");
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Value of `SINGLE_QUOTES` is 13 bytes, more than --max-var-size 12\n"
+    );
+
+    let out = run("--max-env-size", "70");
+
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Variables are 71 bytes, more than --max-env-size 70\n"
+    );
+}
+
+#[test]
+fn cli_lint() {
+    let path = std::env::temp_dir().join(format!("zenv-lint-{}.env", std::process::id()));
+    let file = path.to_str().unwrap();
+
+    std::fs::write(&path, "A=a\nA=b\nB=\"unterminated\nC=c  \n1BAD=x\n").unwrap();
+
+    let out = zenv(&["lint", "-f", file]);
+
+    std::fs::write(&path, "1BAD=x\nGOOD=good\nnot a pair\n2BAD=y\n").unwrap();
+
+    let errors = zenv(&["lint", "-f", file]);
+
+    std::fs::write(&path, "COLOR=red#comment  \n").unwrap();
+
+    let spaced = zenv(&["lint", "-f", file]);
+    let unspaced = zenv(&["lint", "-f", file, "--hash-comments-require-space", "false"]);
+
+    std::fs::write(&path, "A=a\nA+=b\n").unwrap();
+
+    let clean = zenv(&["lint", "-f", file]);
+
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        [
+            format!("{}:5: error: Invalid key - `1BAD=x`", file),
+            format!(
+                "{}:2: warning: Duplicate key, already defined on line 1 - `A=b`",
+                file
+            ),
+            format!(
+                "{}:3: warning: Unterminated quote, the \" is taken literally - `B=\"unterminated`",
+                file
+            ),
+            format!(
+                "{}:4: warning: Trailing whitespace after an unquoted value - `C=c  `",
+                file
+            ),
+            String::new(),
+        ]
+        .join("\n")
+    );
+
+    assert_eq!(errors.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(errors.stdout).unwrap(),
+        [
+            format!("{}:1: error: Invalid key - `1BAD=x`", file),
+            format!("{}:3: error: Expected `KEY=value` - `not a pair`", file),
+            format!("{}:4: error: Invalid key - `2BAD=y`", file),
+            String::new(),
+        ]
+        .join("\n")
+    );
+
+    assert!(spaced.status.success());
+    assert_eq!(
+        String::from_utf8(spaced.stdout).unwrap(),
+        format!(
+            "{}:1: warning: Trailing whitespace after an unquoted value - `COLOR=red#comment  `\n",
+            file
+        )
+    );
+
+    assert!(unspaced.status.success());
+    assert_eq!(String::from_utf8(unspaced.stdout).unwrap(), "");
+
+    assert!(clean.status.success());
+    assert_eq!(String::from_utf8(clean.stdout).unwrap(), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_emit_env_file() {
+    let script = "cat \"$1\"; echo \"$1\"";
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--emit-env-file",
+        "--",
+        "sh",
+        "-c",
+        script,
+        "sh",
+        "{envfile}",
+    ]);
+
+    let mut lines: Vec<&str> = out.lines().collect();
+    let path = lines.pop().unwrap();
+
+    assert_eq!(
+        lines,
+        vec![
+            "BASIC=basic",
+            "DOUBLE_QUOTES=double_quotes",
+            "EMPTY=",
+            "SINGLE_QUOTES=single_quotes"
+        ]
+    );
+
+    // Removed after the command exits
+    assert!(!std::path::Path::new(path).exists());
+
+    // Also in the command, where the file is not executable
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--emit-env-file",
+        "--",
+        "{envfile}",
+    ]);
+    let err = String::from_utf8(out.stderr).unwrap();
+
+    assert_eq!(out.status.code(), Some(126));
+    assert!(err.contains(&std::env::temp_dir().to_string_lossy().into_owned()));
+    assert!(!err.contains("{envfile}"));
+}
+
+#[test]
+fn cli_color() {
+    // Captured i.e. not a terminal, so there is no color by default
+    let out = stdout(&["--help"]);
+    assert!(out.contains("\nUSAGE:\n"));
+    assert!(!out.contains('\x1b'));
+
+    let out = stdout(&["--color", "always", "--help"]);
+    assert!(out.contains("\n\x1b[1;33mUSAGE:\x1b[0m\n"));
+
+    let out = zenv(&[
+        "--color",
+        "always",
+        "-f",
+        "tests/.env.missing",
+        "--",
+        "echo",
+    ]);
+    assert!(String::from_utf8(out.stderr)
+        .unwrap()
+        .starts_with("\x1b[1;31mERROR ::\x1b[0m "));
+
+    let out = zenv(&["-f", "tests/.env.missing", "--", "echo"]);
+    assert!(String::from_utf8(out.stderr)
+        .unwrap()
+        .starts_with("ERROR :: "));
+
+    assert_eq!(zenv(&["--color", "red", "--help"]).status.code(), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_add_prefix() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.base",
+        "--add-prefix",
+        "MYAPP_",
+        "--unset",
+        "HOST",
+        "--",
+        "sh",
+        "-c",
+        "echo ${MYAPP_PORT}_${PORT}_${MYAPP_HOST}",
+    ]);
+
+    assert_eq!(out, "80__\n");
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.base",
+        "--add-prefix",
+        "MYAPP_",
+        "--only",
+        "PORT",
+        "--print",
+    ]);
+
+    assert_eq!(out, "MYAPP_PORT=80\n");
+}
+
+#[test]
+fn cli_expect_sha256() {
+    const BASIC: &str = "ddad85c1219f0c294d19eefc688006da202a88d371cd043b123b54852ba8f9e6";
+    const BASE: &str = "76bef11407ed102b73d4a027247396e8a3c721d8a1a93bf9755cc8677f1fc5c6";
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "-f",
+        "tests/.env.base",
+        "--expect-sha256",
+        BASIC,
+        "--expect-sha256",
+        &BASE.to_uppercase(),
+        "--only",
+        "PORT",
+        "--print",
+    ]);
+
+    assert_eq!(out, "PORT=80\n");
+
+    let out = zenv(&["-f", "tests/.env.base", "--expect-sha256", BASIC, "--print"]);
+
+    assert_eq!(out.status.code(), Some(3));
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8(out.stderr).unwrap().contains(&format!(
+        "tests/.env.base: SHA-256 mismatch, expected {} but found {}",
+        BASIC, BASE
+    )));
+
+    let code = |args: &[&str]| zenv(args).status.code();
+
+    assert_eq!(
+        code(&["-f", "tests/.env.base", "--expect-sha256", "abc", "--print"]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "-f",
+            "tests/.env.basic",
+            "-f",
+            "tests/.env.base",
+            "--expect-sha256",
+            BASIC,
+            "--print"
+        ]),
+        Some(2)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_limits() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--limit-nofile",
+        "64",
+        "--",
+        "sh",
+        "-c",
+        "ulimit -n",
+    ]);
+
+    assert_eq!(out, "64\n");
+
+    // Only the soft limit, so the hard limit is the same as without it
+    let hard = Command::new("sh")
+        .args(["-c", "ulimit -Hn"])
+        .output()
+        .unwrap();
+    let hard = String::from_utf8(hard.stdout).unwrap();
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--limit-nofile",
+        "64",
+        "--",
+        "sh",
+        "-c",
+        "ulimit -Hn",
+    ]);
+
+    assert_eq!(out, hard);
+
+    // Above the hard limit, the command is not run
+    if let Ok(hard) = hard.trim().parse::<u64>() {
+        let limit = (hard + 1).to_string();
+        let out = zenv(&[
+            "-f",
+            "tests/.env.basic",
+            "--limit-nofile",
+            &limit,
+            "--",
+            "echo",
+            "not run",
+        ]);
+
+        assert_eq!(out.status.code(), Some(1));
+        assert_eq!(String::from_utf8(out.stdout).unwrap(), "");
+        assert_eq!(
+            String::from_utf8(out.stderr).unwrap(),
+            format!(
+                "ERROR :: Unable to set --limit-nofile to {}, the hard limit is {}\n",
+                limit, hard
+            )
+        );
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_retry() {
+    let counter = std::env::temp_dir().join(format!("zenv-retry-{}", std::process::id()));
+    let counter = counter.to_str().unwrap();
+    let count = || std::fs::read_to_string(counter).unwrap().lines().count();
+
+    // All the retries fail, so the last exit code is kept
+    let script = format!("echo run >> {}; exit 3", counter);
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--retry",
+        "2",
+        "--retry-delay",
+        "0",
+        "--",
+        "sh",
+        "-c",
+        &script,
+    ]);
+
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(count(), 3);
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "zenv: exited with 3, retrying in 0ns (1/2)\nzenv: exited with 3, retrying in 0ns (2/2)\n"
+    );
+
+    // Stops at the first clean exit
+    std::fs::remove_file(counter).unwrap();
+
+    let script = format!("echo run >> {0}; [ $(wc -l < {0}) -ge 2 ]", counter);
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--retry",
+        "5",
+        "--retry-delay",
+        "10ms",
+        "--",
+        "sh",
+        "-c",
+        &script,
+    ]);
+
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(count(), 2);
+
+    std::fs::remove_file(counter).ok();
+
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--retry-delay",
+        "2h",
+        "--",
+        "true",
+    ]);
+
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn cli_on_spawn_error() {
+    let out = zenv(&[
+        "-f",
+        "tests/.env.basic",
+        "--on-spawn-error",
+        "ignore",
+        "--",
+        "zenv-not-found",
+    ]);
+
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "zenv: `zenv-not-found` is not found, ignored\n"
+    );
+
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--on-spawn-error",
+        "ignore",
+        "--parallel",
+        "zenv-not-found",
+        "sh -c 'echo $BASIC'",
+    ]);
+
+    assert_eq!(out, "basic\n");
+
+    let code = |args: &[&str]| zenv(args).status.code();
+
+    assert_eq!(
+        code(&[
+            "-f",
+            "tests/.env.basic",
+            "--on-spawn-error",
+            "fail",
+            "--",
+            "zenv-not-found"
+        ]),
+        Some(127)
+    );
+    assert_eq!(code(&["--on-spawn-error", "skip", "--", "echo"]), Some(2));
+}
+
+#[test]
+fn cli_compare() {
+    let envs = [("HOST", "localhost"), ("PORT", "8080")];
+
+    let out = zenv_with(&["compare", "-f", "tests/.env.local"], &envs);
+
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "HOST: same\nDIRS: unset\nFLAGS: unset\nURL: unset\n"
+    );
+
+    let out = zenv_with(&["compare", "-f", "tests/.env.base", "--strict"], &envs);
+
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .contains("PORT: differs\n"));
+
+    // The values are compared after the substitution
+    let envs = [
+        ("HOST", "localhost"),
+        ("DIRS", ":/extra/bin"),
+        ("FLAGS", "--verbose"),
+        ("URL", "http://localhost:"),
+    ];
+
+    let out = zenv_with(
+        &[
+            "compare",
+            "-f",
+            "tests/.env.local",
+            "-x",
+            "--no-env-fallback",
+            "--strict",
+        ],
+        &envs,
+    );
+
+    assert!(out.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_append_path() {
+    let print = |args: &[&str]| {
+        let out = zenv_with(args, &[("ZENV_DIRS", "/inherited")]);
+
+        assert!(out.status.success());
+
+        String::from_utf8(out.stdout).unwrap()
+    };
+
+    // On top of the value of the files
+    let out = print(&[
+        "-f",
+        "tests/.env.base",
+        "--append-path",
+        "DIRS=/opt/bin",
+        "--prepend-path",
+        "DIRS=/first/bin",
+        "--append-path",
+        "DIRS=/last/bin",
+        "--print",
+    ]);
+
+    assert_eq!(
+        out,
+        "HOST=example.com\nPORT=80\nDIRS=/first/bin:/usr/bin:/opt/bin:/last/bin\n"
+    );
+
+    // Or the inherited value
+    let out = print(&[
+        "-f",
+        "tests/.env.basic",
+        "--prepend-path",
+        "ZENV_DIRS=/opt/bin",
+        "--",
+        "sh",
+        "-c",
+        "echo $ZENV_DIRS",
+    ]);
+
+    assert_eq!(out, "/opt/bin:/inherited\n");
+
+    // Or created
+    let out = print(&[
+        "-f",
+        "tests/.env.basic",
+        "--append-path",
+        "ZENV_NEW=/opt/bin",
+        "--",
+        "sh",
+        "-c",
+        "echo $ZENV_NEW",
+    ]);
+
+    assert_eq!(out, "/opt/bin\n");
+}
+
+#[test]
+fn cli_fail_on_override() {
+    let envs = [("HOST", "from_env"), ("PORT", "80"), ("DIRS", "/bin")];
+    let args = |flag| ["-f", "tests/.env.base", flag, "--print"];
+
+    let out = zenv_with(&args("--fail-on-override"), &envs);
+
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Variables override the inherited values - HOST, DIRS\n"
+    );
+
+    let out = zenv_with(&args("--warn-on-override"), &envs);
+
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "zenv: `HOST` overrides the inherited value\nzenv: `DIRS` overrides the inherited value\n"
+    );
+
+    // Not inherited, so nothing is overridden
+    let out = zenv_with(
+        &[
+            "-f",
+            "tests/.env.base",
+            "--inherit",
+            "PORT",
+            "--fail-on-override",
+            "--print",
+        ],
+        &envs,
+    );
+
+    assert!(out.status.success());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn cli_toml() {
+    let out = stdout(&[
+        "-f",
+        "tests/.env.basic",
+        "--toml",
+        "tests/Config.toml",
+        "-x",
+        "--json",
+    ]);
+
+    assert_eq!(
+        out,
+        r#"{
+  "BASIC": "basic",
+  "EMPTY": "",
+  "SINGLE_QUOTES": "single_quotes",
+  "DOUBLE_QUOTES": "double_quotes",
+  "HOST": "localhost",
+  "PORT": "8080",
+  "URL": "http://${HOST}:${PORT}",
+  "LITERAL": "C:\\tools",
+  "ESCAPED": "tab\there é",
+  "QUOTED": "say \"hi\"",
+  "MULTI": "first\nsecond",
+  "MIXED": "it's \"x\"\nand $HOME"
+}
+"#
+    );
+
+    let out = zenv(&["--toml", "tests/Config.invalid.toml", "--print"]);
+
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: tests/Config.invalid.toml: `PORT` in [env] is not a string, but integer\n"
+    );
+}
+
+#[test]
+fn cli_file_home() {
+    let home = concat!(env!("CARGO_MANIFEST_DIR"), "/tests");
+
+    let out = zenv_with(&["get", "-f", "~/.env.basic", "BASIC"], &[("HOME", home)]);
+
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "basic\n");
+
+    // Only a leading `~/`
+    let out = zenv_with(
+        &["get", "-f", "tests/~/.env.basic", "BASIC"],
+        &[("HOME", home)],
+    );
+
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        "ERROR :: Unable to find file - tests/~/.env.basic\n"
+    );
+}
+
+#[cfg(feature = "crypto")]
+#[test]
+fn cli_encrypt() {
+    let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    let vault = std::env::temp_dir().join(format!("zenv-{}.env.vault", std::process::id()));
+    let vault = vault.to_str().unwrap();
+
+    stdout(&[
+        "encrypt",
+        "-f",
+        "tests/.env.print",
+        "--decrypt-key",
+        key,
+        "--out",
+        vault,
+    ]);
+
+    let encrypted = std::fs::read_to_string(vault).unwrap();
+
+    assert!(encrypted.starts_with("ZENV-AES-256-GCM-V1\n"));
+    assert!(!encrypted.contains("basic"));
+
+    // Same as the plain file, with the key from the option or the environment
+    let out = stdout(&["-f", vault, "--decrypt-key", key, "--print"]);
+
+    assert_eq!(out, stdout(&["-f", "tests/.env.print", "--print"]));
+
+    let out = zenv_with(&["-f", vault, "--print"], &[("ZENV_KEY", key)]);
+
+    assert_eq!(
+        out.stdout,
+        zenv(&["-f", "tests/.env.print", "--print"]).stdout
+    );
+
+    let wrong = key.replace("00", "ff");
+    let out = zenv(&["-f", vault, "--decrypt-key", &wrong, "--print"]);
+
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8(out.stderr).unwrap(),
+        format!(
+            "ERROR :: {}: Unable to decrypt the file, the key is wrong or the file is modified\n",
+            vault
+        )
+    );
+
+    let out = zenv(&["-f", vault, "--decrypt-key", "abc", "--print"]);
+
+    assert_eq!(out.status.code(), Some(2));
+
+    std::fs::remove_file(vault).unwrap();
+}
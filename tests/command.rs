@@ -0,0 +1,56 @@
+#![cfg(feature = "std")]
+
+use std::{collections::HashMap, ffi::OsStr, process::Stdio};
+
+use zenv::{build_command, exe_candidates, CommandOptions};
+
+#[test]
+fn exe_candidates_without_extension() {
+    let names = exe_candidates(OsStr::new("node_modules/.bin/vite"));
+
+    assert_eq!(
+        names,
+        vec![
+            "node_modules/.bin/vite",
+            "node_modules/.bin/vite.exe",
+            "node_modules/.bin/vite.cmd",
+            "node_modules/.bin/vite.bat"
+        ]
+    )
+}
+
+#[test]
+fn exe_candidates_with_extension() {
+    let names = exe_candidates(OsStr::new("C:\\tools\\terraform.exe"));
+
+    assert_eq!(names, vec!["C:\\tools\\terraform.exe"])
+}
+
+#[cfg(unix)]
+#[test]
+fn build_command_inherit() {
+    let vars: HashMap<_, _> = vec![("ZENV_BUILD".to_string(), "from_file".to_string())]
+        .into_iter()
+        .collect();
+
+    let opts = CommandOptions {
+        inherit: vec!["PATH".to_string()],
+        ..Default::default()
+    };
+
+    let script = "echo \"$ZENV_BUILD ${PATH:+path} ${HOME:-no_home}\"";
+    let mut cmd = build_command(
+        OsStr::new("sh"),
+        &["-c".into(), script.into()],
+        &vars,
+        &opts,
+    );
+
+    // The stdio is inherited by default
+    let out = cmd.stdout(Stdio::piped()).output().unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "from_file path no_home\n"
+    );
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 use zenv::*;
 
 pub fn parse(line: &str) -> Option<(&str, String)> {
@@ -28,6 +30,37 @@ fn empty_value() {
     assert_eq!(res, ("EMPTY", "".to_string()))
 }
 
+#[test]
+fn empty_value_with_comment() {
+    let res = parse("EMPTY= # intentionally blank").unwrap();
+
+    assert_eq!(res, ("EMPTY", "".to_string()))
+}
+
+#[test]
+fn empty_value_with_space() {
+    let res = parse("EMPTY=    ").unwrap();
+
+    assert_eq!(res, ("EMPTY", "".to_string()))
+}
+
+#[test]
+fn empty_value_quoted() {
+    assert_eq!(parse("EMPTY=\"\"").unwrap(), ("EMPTY", "".to_string()));
+    assert_eq!(parse("EMPTY=''").unwrap(), ("EMPTY", "".to_string()));
+}
+
+#[test]
+fn missing_equal() {
+    assert_eq!(Line::from("NOT_A_PAIR"), Line::Empty);
+    assert_eq!(Line::from("NOT_A_PAIR # comment"), Line::Empty);
+
+    let lines = Lines::from("EMPTY=\nNOT_A_PAIR");
+
+    assert_eq!(lines.get("EMPTY"), Some(String::new()));
+    assert_eq!(lines.get("NOT_A_PAIR"), None);
+}
+
 #[test]
 fn single_quotes() {
     let res = parse("SINGLE_QUOTES='single_quotes'").unwrap();
@@ -210,6 +243,32 @@ fn retain_trailing_squote() {
     )
 }
 
+#[test]
+fn trailing_after_quote() {
+    use zenv::{Lines, ParseOptions};
+
+    let res = parse("TRAILING=\"a\" b").unwrap();
+    assert_eq!(res, ("TRAILING", "a b".to_string()));
+
+    let res = parse("TRAILING='a'b/c # comment").unwrap();
+    assert_eq!(res, ("TRAILING", "ab/c".to_string()));
+
+    // Only a comment, or whitespace, after the closing quote
+    let res = parse("TRAILING=\"a\"# comment").unwrap();
+    assert_eq!(res, ("TRAILING", "a".to_string()));
+
+    let opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let err = Lines::parse("A=1\nTRAILING=\"a\" b", &opts).unwrap_err();
+    assert_eq!(err.line, 2);
+    assert_eq!(err.msg, "Unexpected `b` after the closing quote");
+
+    assert!(Lines::parse("TRAILING=\"a\"  # comment", &opts).is_ok());
+}
+
 #[test]
 fn retain_inner_quotes_as_string() {
     let res = parse(r#"RETAIN_INNER_QUOTES_AS_STRING='{"foo": "bar"}'"#).unwrap();
@@ -291,3 +350,692 @@ fn comment_at_end_dquote() {
         )
     )
 }
+
+#[test]
+fn backticks() {
+    let res = Line::from("BACKTICKS=`back ${ticks} # not a comment`");
+
+    assert_eq!(
+        res,
+        Line::KeyVal(KeyVal {
+            k: "BACKTICKS",
+            v: "back ${ticks} # not a comment".to_string(),
+            q: Quote::Backtick
+        })
+    )
+}
+
+#[test]
+fn retain_leading_backtick() {
+    let res = parse("RETAIN_LEADING_BACKTICK=`retained_backtick").unwrap();
+
+    assert_eq!(
+        res,
+        ("RETAIN_LEADING_BACKTICK", "`retained_backtick".to_string())
+    )
+}
+
+#[test]
+fn heredoc_unterminated() {
+    let opts = ParseOptions {
+        heredoc: true,
+        ..Default::default()
+    };
+    let err = Lines::parse("BASIC=basic\nKEY=<<EOF\nnot closed", &opts).unwrap_err();
+
+    assert_eq!(err.line, 2);
+    assert_eq!(err.content, "KEY=<<EOF");
+}
+
+#[test]
+fn heredoc_disabled() {
+    let parsed = Lines::from("KEY<<EOF").to_hash_map();
+
+    assert!(parsed.is_empty());
+
+    let parsed = Lines::from("KEY=<<EOF").to_hash_map();
+
+    assert_eq!(parsed.get("KEY").unwrap(), "<<EOF");
+}
+
+#[test]
+fn hash_requires_space() {
+    let res = parse("COLOR=#fff").unwrap();
+
+    assert_eq!(res, ("COLOR", "#fff".to_string()));
+
+    let res = parse("URL=http://host/#anchor # comment").unwrap();
+
+    assert_eq!(res, ("URL", "http://host/#anchor".to_string()));
+}
+
+#[test]
+fn hash_without_space() {
+    let opts = ParseOptions {
+        hash_comments_require_space: false,
+        ..Default::default()
+    };
+
+    let res = Line::parse("KEY=#comment", &opts);
+
+    assert_eq!(
+        res,
+        Line::KeyVal(KeyVal {
+            k: "KEY",
+            v: "".to_string(),
+            q: Quote::No
+        })
+    );
+
+    let res = Line::parse("URL=http://host/#anchor", &opts);
+
+    assert_eq!(
+        res,
+        Line::KeyVal(KeyVal {
+            k: "URL",
+            v: "http://host/".to_string(),
+            q: Quote::No
+        })
+    );
+}
+
+#[test]
+fn strict_missing_equal() {
+    let opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let err = Lines::parse("BASIC=basic\n\n  # comment\nNOT_A_PAIR", &opts).unwrap_err();
+
+    assert_eq!(err.line, 4);
+    assert_eq!(err.content, "NOT_A_PAIR");
+
+    let parsed = Lines::from("BASIC=basic\nNOT_A_PAIR").to_hash_map();
+
+    assert_eq!(parsed.len(), 1);
+}
+
+#[test]
+fn strict_invalid_key() {
+    let opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    for line in ["1KEY=val", "MY KEY=val", "=val", "KEY-NAME=val"] {
+        let err = Lines::parse(line, &opts).unwrap_err();
+
+        assert_eq!(err.line, 1);
+        assert_eq!(err.content, line);
+    }
+
+    assert!(Lines::parse("_KEY_1=val", &opts).is_ok());
+}
+
+#[test]
+fn strict_brackets() {
+    let mut opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    assert!(Lines::parse("SERVERS[0]=a", &opts).is_err());
+
+    opts.allow_brackets = true;
+
+    let lines = Lines::parse("SERVERS[0]=a\nSERVERS[1]=b", &opts).unwrap();
+
+    assert_eq!(lines.get("SERVERS[1]"), Some("b".to_string()));
+
+    for line in ["SERVERS[]=a", "SERVERS[x]=a", "[0]=a", "SERVERS[0]X=a"] {
+        assert!(Lines::parse(line, &opts).is_err(), "{}", line);
+    }
+}
+
+#[test]
+fn strict_relaxed_keys() {
+    let mut opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    const LINES: &str = "spring.datasource.url=jdbc:h2:mem\nfeature-flag=on\nconfig/path=a";
+
+    assert!(Lines::parse(LINES, &opts).is_err());
+
+    opts.relaxed_keys = true;
+
+    let parsed = Lines::parse(LINES, &opts).unwrap().to_hash_map();
+
+    assert_eq!(parsed.get("spring.datasource.url").unwrap(), "jdbc:h2:mem");
+    assert_eq!(parsed.get("feature-flag").unwrap(), "on");
+    assert_eq!(parsed.get("config/path").unwrap(), "a");
+
+    for line in [".key=val", "-key=val", "my key=val"] {
+        assert!(Lines::parse(line, &opts).is_err(), "{}", line);
+    }
+}
+
+#[test]
+fn conditionals() {
+    const LINES: &str = r#"
+PROFILE=prod
+# zenv:if PROFILE=prod
+HOST=prod.example.com
+# zenv:if REGION==eu
+REGION_HOST=eu.example.com
+# zenv:endif
+# zenv:endif
+# zenv:if PROFILE=dev
+HOST=localhost
+# zenv:endif
+"#;
+
+    let opts = ParseOptions {
+        conditionals: true,
+        ..Default::default()
+    };
+
+    let parsed = Lines::parse(LINES, &opts).unwrap().to_hash_map();
+
+    assert_eq!(parsed.get("HOST").unwrap(), "prod.example.com");
+    assert_eq!(parsed.get("REGION_HOST"), None);
+
+    // Just comments when disabled
+    let parsed = Lines::parse(LINES, &ParseOptions::default())
+        .unwrap()
+        .to_hash_map();
+
+    assert_eq!(parsed.get("HOST").unwrap(), "localhost");
+    assert_eq!(parsed.get("REGION_HOST").unwrap(), "eu.example.com");
+}
+
+#[test]
+fn conditionals_from_env() {
+    std::env::set_var("ZENV_CONDITIONAL", "yes");
+
+    let opts = ParseOptions {
+        conditionals: true,
+        ..Default::default()
+    };
+
+    let lines = "# zenv:if ZENV_CONDITIONAL=yes\nFOUND=found\n# zenv:endif";
+    let parsed = Lines::parse(lines, &opts).unwrap().to_hash_map();
+
+    assert_eq!(parsed.get("FOUND").unwrap(), "found");
+}
+
+#[test]
+fn conditionals_unbalanced() {
+    let opts = ParseOptions {
+        conditionals: true,
+        ..Default::default()
+    };
+
+    let err = Lines::parse("A=a\n# zenv:if A=a\nB=b", &opts).unwrap_err();
+    assert_eq!(err.line, 2);
+
+    let err = Lines::parse("A=a\n# zenv:endif", &opts).unwrap_err();
+    assert_eq!(err.line, 2);
+
+    let err = Lines::parse("# zenv:if A\n# zenv:endif", &opts).unwrap_err();
+    assert_eq!(err.line, 1);
+}
+
+#[test]
+fn line_continuation() {
+    const LINES: &str = "LIST=a,b,\\\nc,d,\\\n  e\nNEXT=next\nCOMMENT=a # not continued \\\nQUOTED='a\\'\nESCAPED=a\\\\\nLAST=last\\";
+
+    let opts = ParseOptions {
+        line_continuation: true,
+        ..Default::default()
+    };
+
+    let lines = Lines::parse(LINES, &opts).unwrap();
+
+    assert_eq!(lines.get("LIST").unwrap(), "a,b,c,d,  e");
+    assert_eq!(lines.get("NEXT").unwrap(), "next");
+    assert_eq!(lines.get("COMMENT").unwrap(), "a");
+    assert_eq!(lines.get("QUOTED").unwrap(), "a\\");
+    assert_eq!(lines.get("ESCAPED").unwrap(), "a\\\\");
+    assert_eq!(lines.get("LAST").unwrap(), "last\\");
+
+    // The joined lines are a part of the pair
+    let spans: Vec<_> = lines.entries().map(|x| (x.line, x.end)).collect();
+
+    assert_eq!(spans[..3], [(1, 3), (4, 4), (5, 5)]);
+
+    // Taken literally when disabled
+    let lines = Lines::parse(LINES, &ParseOptions::default()).unwrap();
+
+    assert_eq!(lines.get("LIST").unwrap(), "a,b,\\");
+}
+
+#[test]
+fn indented_comment() {
+    let res = Line::from("   # COMMENT=comment");
+
+    assert_eq!(res, Line::Empty)
+}
+
+#[test]
+fn lint_trailing_whitespace() {
+    let lints = lint(
+        "A=a \nB='b '  \nC=c # comment \nD=\t\nE=e",
+        &ParseOptions::default(),
+    );
+
+    let lines: Vec<usize> = lints.iter().map(|x| x.line).collect();
+
+    assert_eq!(lines, vec![1, 4]);
+}
+
+#[test]
+fn append() {
+    let res = Line::from("LIST+='a b'");
+
+    assert_eq!(
+        res,
+        Line::Append(KeyVal {
+            k: "LIST",
+            v: "a b".to_string(),
+            q: Quote::Single
+        })
+    );
+
+    let parsed = Lines::from("LIST=a\nLIST+=,b\nLIST+=,c").to_hash_map();
+
+    assert_eq!(parsed.get("LIST").unwrap(), "a,b,c");
+}
+
+#[test]
+fn expand_default_from_env() {
+    std::env::set_var("ZENV_DEFAULT_PORT", "8080");
+    std::env::set_var("ZENV_SELF_PORT", "9090");
+
+    const LINES: &str = r#"
+PORT="${ZENV_OUTER_PORT:-${ZENV_DEFAULT_PORT}}"
+ZENV_SELF_PORT="${ZENV_SELF_PORT:-${ZENV_DEFAULT_PORT}}"
+ZENV_NO_PORT="${ZENV_NO_PORT:-${ZENV_DEFAULT_PORT}}"
+"#;
+
+    let parsed = Lines::from(LINES).expand();
+
+    // Both only in the environment, and the outer is not set
+    assert_eq!(parsed.get("PORT").unwrap(), "8080");
+
+    // The key itself is looked up in the environment, not in its own definition
+    assert_eq!(parsed.get("ZENV_SELF_PORT").unwrap(), "9090");
+    assert_eq!(parsed.get("ZENV_NO_PORT").unwrap(), "8080");
+}
+
+#[test]
+fn expand_escaped_brace() {
+    const LINES: &str = r#"
+DEFAULT="${ZENV_NO_DEFAULT:-a\}b}"
+NESTED="${ZENV_NO_A:-${ZENV_NO_B:-a\}b}c\}}"
+REPLACED="${NESTED/\}/)}"
+OUTSIDE="\}${ZENV_NO_C:-c}"
+"#;
+
+    let parsed = Lines::from(LINES).expand();
+
+    assert_eq!(parsed.get("DEFAULT").unwrap(), "a}b");
+    assert_eq!(parsed.get("NESTED").unwrap(), "a}bc}");
+    assert_eq!(parsed.get("REPLACED").unwrap(), "a)bc}");
+
+    // Only inside `${...}`
+    assert_eq!(parsed.get("OUTSIDE").unwrap(), "\\}c");
+}
+
+#[test]
+fn expand_self_reference() {
+    let parsed = Lines::from("DIRS=/usr/bin\nDIRS=\"${DIRS}:/extra/bin\"").expand();
+
+    assert_eq!(parsed.get("DIRS").unwrap(), "/usr/bin:/extra/bin");
+}
+
+#[test]
+fn expand_forward_chain() {
+    let opts = ExpandOptions {
+        env_fallback: false,
+        ..Default::default()
+    };
+
+    // Every line references the next one, so each level is only known after the previous pass
+    let lines = Lines::from("A=\"${B}\"\nB=\"${C}\"\nC=\"${D}\"\nD=d");
+    let parsed = lines.expand_with(&opts);
+
+    assert_eq!(parsed.get("A").unwrap(), "d");
+    assert_eq!(parsed.get("B").unwrap(), "d");
+    assert_eq!(parsed.get("C").unwrap(), "d");
+
+    // A cycle doesn't loop forever
+    let parsed = Lines::from("A=\"${B}\"\nB=\"${A}\"").expand_with(&opts);
+
+    assert_eq!(parsed.len(), 2);
+}
+
+#[test]
+fn from_pairs() {
+    let pairs = vec![
+        ("B".to_string(), "b".to_string()),
+        ("A".to_string(), "'quoted' # not a comment".to_string()),
+        ("B".to_string(), "last".to_string()),
+    ];
+
+    let lines = Lines::from_pairs(pairs.clone());
+
+    assert_eq!(lines.to_vec(), pairs);
+    assert_eq!(lines.get("A").unwrap(), "'quoted' # not a comment");
+    assert_eq!(lines.get("B").unwrap(), "last");
+}
+
+#[test]
+fn lint_duplicate_and_unterminated() {
+    let lints = lint(
+        "A=a\nA+=b\nA=c\nB='b\nC=\"c\" # \"\nD=`d",
+        &ParseOptions::default(),
+    );
+
+    let found: Vec<_> = lints.iter().map(|x| (x.line, x.msg.as_str())).collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (3, "Duplicate key, already defined on line 2"),
+            (4, "Unterminated quote, the ' is taken literally"),
+            (6, "Unterminated quote, the ` is taken literally"),
+        ]
+    );
+}
+
+#[test]
+fn lines_eq() {
+    let opts = ParseOptions {
+        heredoc: true,
+        ..Default::default()
+    };
+
+    let heredoc = Lines::parse("CERT<<END\na\nb\nEND\nLIST+=x", &opts).unwrap();
+    let expected = Lines::new(vec![
+        Line::KeyVal(KeyVal {
+            k: "CERT",
+            v: "a\nb".to_string(),
+            q: Quote::No,
+        }),
+        Line::from("LIST+=x"),
+    ]);
+
+    assert_eq!(heredoc, expected);
+
+    // `+=` is not the same as `=`
+    assert_ne!(Lines::from("LIST+=x"), Lines::from("LIST=x"));
+}
+
+#[test]
+fn keep_quotes() {
+    let opts = ParseOptions {
+        keep_quotes: true,
+        ..Default::default()
+    };
+
+    let kv = |line| match Line::parse(line, &opts) {
+        Line::KeyVal(kv) => (kv.q, kv.v),
+        x => panic!("{:?}", x),
+    };
+
+    assert_eq!(kv("A=\"value\""), (Quote::Double, "\"value\"".to_string()));
+    assert_eq!(
+        kv("A='value' # comment"),
+        (Quote::Single, "'value'".to_string())
+    );
+    assert_eq!(kv("A=`value`"), (Quote::Backtick, "`value`".to_string()));
+    assert_eq!(kv("A=\"a\\nb\""), (Quote::Double, "\"a\\nb\"".to_string()));
+
+    // Unquoted or unterminated values are parsed as usual
+    assert_eq!(kv("A=value # comment"), (Quote::No, "value".to_string()));
+    assert_eq!(kv("A=\"value"), (Quote::No, "\"value".to_string()));
+}
+
+#[test]
+fn lines_effective() {
+    let lines = Lines::from("A=a\nB=b\nC=c\nA=again\nB+=,b\nD=d\nC=c2\nB+=,c\nE+=e");
+    let effective = lines.effective();
+
+    assert_eq!(
+        effective.to_vec(),
+        vec![
+            ("A".to_string(), "again".to_string()),
+            ("D".to_string(), "d".to_string()),
+            ("C".to_string(), "c2".to_string()),
+            ("B".to_string(), "b,b,c".to_string()),
+            ("E".to_string(), "e".to_string())
+        ]
+    );
+    assert_eq!(effective.to_hash_map(), lines.to_hash_map());
+
+    // Every key is a single pair at its last line
+    let lines: Vec<_> = effective
+        .entries()
+        .map(|x| (x.key, x.line, x.append))
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            ("A", 4, false),
+            ("D", 6, false),
+            ("C", 7, false),
+            ("B", 8, false),
+            ("E", 9, false)
+        ]
+    );
+
+    // An override drops the appended values before it
+    let lines = Lines::from("A=a\nA+=b\nB=b\nA=c\nA+=d");
+
+    assert_eq!(lines.effective(), Lines::from("B=b\nA=cd"));
+
+    // The joined value keeps the quote of the `key=val` pair
+    let lines = Lines::from("V=v\nA=\"$V\"\nB=b\nA+=\"_${V}\"");
+
+    assert_eq!(lines.effective(), Lines::from("V=v\nB=b\nA=\"$V_${V}\""));
+}
+
+#[test]
+fn quoted_keys() {
+    let opts = ParseOptions {
+        allow_quoted_keys: true,
+        strict: true,
+        ..Default::default()
+    };
+
+    let lines = Lines::parse(
+        "\"weird key\"=\"weird value\"\n'a=b' += c\n  \"LIST\"+=,d\nLIST=a",
+        &opts,
+    )
+    .unwrap();
+
+    assert_eq!(
+        lines.to_vec(),
+        vec![
+            ("weird key".to_string(), "weird value".to_string()),
+            ("a=b".to_string(), "c".to_string()),
+            ("LIST".to_string(), ",d".to_string()),
+            ("LIST".to_string(), "a".to_string())
+        ]
+    );
+
+    // Only with the `=` right after the closing quote
+    let err = Lines::parse("\"weird\" key=value", &opts).unwrap_err();
+    assert_eq!(err.msg, "Invalid key");
+
+    // Rejected by default
+    let opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let err = Lines::parse("\"weird key\"=\"weird value\"", &opts).unwrap_err();
+    assert_eq!(err.msg, "Invalid key");
+}
+
+#[test]
+fn expand_sigil() {
+    let opts = ExpandOptions {
+        env_fallback: false,
+        sigil: '%',
+        ..Default::default()
+    };
+
+    let lines = Lines::from(
+        "BASIC=basic\nENCLOSED=\"%BASIC%_enclosed\"\nDEFAULT=\"%MISSING:-%\"\nOPS=\"%BASIC:1:3%-%BASIC/b/B%\"\nLITERAL=\"100%% $BASIC ${BASIC} 50% off, 20% more %\"\nUNSET=\"[%MISSING%]\"",
+    );
+
+    let expanded = lines.expand_with(&opts);
+
+    assert_eq!(expanded.get("ENCLOSED").unwrap(), "basic_enclosed");
+    assert_eq!(expanded.get("DEFAULT").unwrap(), "");
+    assert_eq!(expanded.get("OPS").unwrap(), "asi-Basic");
+    assert_eq!(
+        expanded.get("LITERAL").unwrap(),
+        "100% $BASIC ${BASIC} 50% off, 20% more %"
+    );
+    assert_eq!(expanded.get("UNSET").unwrap(), "[]");
+
+    let vars = expanded.into_iter().collect();
+    let opts = ExpandOptions { sigil: '@', ..opts };
+
+    assert_eq!(
+        substitute("prefix=@BASIC@ mail=a@b.c @ENCLOSED:-x@", &vars, &opts),
+        "prefix=basic mail=a@b.c basic_enclosed"
+    );
+}
+
+#[test]
+fn lines_from_lines() {
+    let src = "A=a\n\n# B=commented\nB='b # quoted'\nA+=_appended\nINVALID";
+    let received: Vec<String> = src.lines().map(String::from).collect();
+
+    let lines = Lines::from_lines(&received);
+
+    assert_eq!(lines, Lines::from(src));
+    assert_eq!(
+        lines.to_vec(),
+        vec![
+            ("A".to_string(), "a".to_string()),
+            ("B".to_string(), "b # quoted".to_string()),
+            ("A".to_string(), "a_appended".to_string()),
+        ]
+    );
+
+    // Owned, so it outlives the lines
+    let lines = Lines::from_lines(received);
+
+    assert_eq!(lines.get("A"), Some("a_appended".to_string()));
+    assert!(Lines::from_lines(Vec::<&str>::new()).is_empty());
+}
+
+#[test]
+fn lines_empty() {
+    let mut lines = Lines::empty();
+
+    assert!(lines.is_empty());
+    assert_eq!(lines, Lines::default());
+
+    lines.extend(vec![KeyVal {
+        k: "A",
+        v: "a".to_string(),
+        q: Quote::No,
+    }]);
+    lines.merge(Lines::from_pairs(vec![("B".to_string(), "b".to_string())]));
+    lines.extend(vec![KeyVal {
+        k: "A",
+        v: "again".to_string(),
+        q: Quote::No,
+    }]);
+
+    let map = lines.to_hash_map();
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["A"], "again");
+    assert_eq!(map["B"], "b");
+}
+
+#[test]
+fn lines_extend() {
+    let mut lines = Lines::from("A=a\nB=\"$A\"");
+
+    lines.extend(vec![KeyVal {
+        k: "A",
+        v: "again".to_string(),
+        q: Quote::No,
+    }]);
+    lines.extend(vec![KeyVal {
+        k: "C",
+        v: "${A}_c".to_string(),
+        q: Quote::Double,
+    }]);
+
+    assert_eq!(
+        lines.to_vec(),
+        vec![
+            ("A".to_string(), "a".to_string()),
+            ("B".to_string(), "$A".to_string()),
+            ("A".to_string(), "again".to_string()),
+            ("C".to_string(), "${A}_c".to_string())
+        ]
+    );
+
+    let expanded = lines.expand();
+
+    assert_eq!(expanded.get("A").unwrap(), "again");
+    assert_eq!(expanded.get("C").unwrap(), "again_c");
+}
+
+#[test]
+fn lines_into_hash_map() {
+    const LINES: &str = "A=a\nLIST=a\nA=again\nLIST+=,b\nNEW+=new\nB='${A}'";
+
+    let lines = Lines::from(LINES);
+    let borrowed = lines.to_hash_map();
+
+    assert_eq!(lines.into_hash_map(), borrowed);
+    assert_eq!(borrowed.get("A").unwrap(), "again");
+    assert_eq!(borrowed.get("LIST").unwrap(), "a,b");
+    assert_eq!(borrowed.get("NEW").unwrap(), "new");
+}
+
+#[cfg(all(feature = "mmap", unix))]
+#[test]
+fn lines_mmap() {
+    // SAFETY: The test files are never modified
+    let map = unsafe { Mmap::open("tests/.env.basic") }.unwrap();
+    let lines = Lines::parse(map.as_str().unwrap(), &ParseOptions::default()).unwrap();
+
+    assert_eq!(
+        lines.to_hash_map(),
+        Lines::from(
+            std::fs::read_to_string("tests/.env.basic")
+                .unwrap()
+                .as_str()
+        )
+        .to_hash_map()
+    );
+
+    // Can't be mapped, but is still a valid file
+    let empty = std::env::temp_dir().join("zenv-mmap-empty.env");
+    std::fs::write(&empty, "").unwrap();
+
+    let map = unsafe { Mmap::open(&empty) }.unwrap();
+
+    assert_eq!(map.as_str().unwrap(), "");
+    std::fs::remove_file(&empty).ok();
+
+    let map = unsafe { Mmap::open("tests/.env.latin1") }.unwrap();
+
+    assert!(map.as_str().is_err());
+    assert!(unsafe { Mmap::open("tests/.env.missing") }.is_err());
+}
@@ -1,4 +1,6 @@
-use zenv::{zenv, Zenv};
+#![cfg(feature = "std")]
+
+use zenv::{zenv, Compat, Zenv};
 
 #[test]
 fn zenv_basic() {
@@ -36,6 +38,41 @@ fn zenv_expanded() {
 
     assert_eq!(z.get("NO_SYSTEM_VAR").unwrap(), "_dont_exist");
     assert_ne!(z.get("SYSTEM_VAR").unwrap(), "_exist");
+
+    assert_eq!(z.get("INDIRECT").unwrap(), "basic_indirect");
+    assert_eq!(z.get("NO_INDIRECT").unwrap(), "_no_indirect");
+
+    assert_eq!(z.get("REPLACE_ALL").unwrap(), "api_example_com");
+    assert_eq!(z.get("REPLACE_FIRST").unwrap(), "api_example.com");
+    assert_eq!(z.get("REPLACE_DELETE").unwrap(), "api.com");
+    assert_eq!(z.get("REPLACE_MISSING").unwrap(), "api.example.com");
+
+    assert_eq!(z.get("DEFAULT").unwrap(), "default");
+    assert_eq!(z.get("DEFAULT_EMPTY").unwrap(), "default");
+    assert_eq!(z.get("DEFAULT_UNSET").unwrap(), "default");
+    assert_eq!(z.get("DEFAULT_NESTED").unwrap(), "basic_nested");
+    assert_eq!(z.get("TRAILING").unwrap(), "basic$");
+
+    assert_eq!(z.get("OFFSET").unwrap(), "example.com");
+    assert_eq!(z.get("OFFSET_LENGTH").unwrap(), "example");
+    assert_eq!(z.get("OFFSET_EXCEEDS").unwrap(), "");
+}
+
+#[test]
+fn zenv_compat_bash() {
+    let z = Zenv::new("tests/.env.expanded", true)
+        .compat(Compat::Bash)
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("DOUBLE_EXPANDED").unwrap(), "basic-basic-expanded");
+    assert_eq!(z.get("DEFAULT").unwrap(), "default");
+    assert_eq!(z.get("DEFAULT_EMPTY").unwrap(), "default");
+    assert_eq!(z.get("DEFAULT_UNSET").unwrap(), "");
+    assert_eq!(z.get("DEFAULT_NESTED").unwrap(), "basic_nested");
+    assert_eq!(z.get("ESCAPED").unwrap(), "$BASIC");
+    assert_eq!(z.get("POSITIONAL").unwrap(), "abc");
+    assert_eq!(z.get("TRAILING").unwrap(), "basic$");
 }
 
 #[test]
@@ -60,3 +97,295 @@ fn zenv_macro_expanded() {
     assert_eq!(var_os("EXPANDED").unwrap(), "basic-expanded");
     assert_eq!(var_os("DOUBLE_EXPANDED").unwrap(), "basic-basic-expanded");
 }
+
+#[test]
+fn zenv_no_env_fallback() {
+    let z = Zenv::new("tests/.env.expanded", true)
+        .env_fallback(false)
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("DOUBLE_EXPANDED").unwrap(), "basic-basic-expanded");
+    assert_eq!(z.get("SYSTEM_VAR").unwrap(), "_exist");
+    assert_eq!(z.get("NO_SYSTEM_VAR").unwrap(), "_dont_exist");
+}
+
+#[test]
+fn zenv_try_from_path() {
+    use std::{convert::TryFrom, io::ErrorKind, path::Path};
+
+    let z = Zenv::try_from(Path::new("tests/.env.basic")).unwrap();
+
+    assert_eq!(z.parse().unwrap().get("BASIC").unwrap(), "basic");
+
+    // Without the substitution, unless it is enabled
+    let z = Zenv::try_from(Path::new("tests/.env.expanded")).unwrap();
+
+    assert_eq!(
+        z.parse().unwrap().get("EXPANDED_NEW").unwrap(),
+        "${BASIC}_expanded"
+    );
+
+    let parsed = z.substitution(true).parse().unwrap();
+
+    assert_eq!(parsed.get("EXPANDED_NEW").unwrap(), "basic_expanded");
+
+    let err = Zenv::try_from(Path::new("tests/.env.missing").to_path_buf()).unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn zenv_key_transform() {
+    use zenv::KeyTransform;
+
+    let z = Zenv::new("tests/.env.expanded", true)
+        .key_transform(KeyTransform::Lower)
+        .env_fallback(false)
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("basic").unwrap(), "basic");
+    assert_eq!(z.get("BASIC"), None);
+
+    // References are resolved after the keys are transformed
+    assert_eq!(z.get("expanded_new").unwrap(), "_expanded");
+}
+
+#[test]
+fn zenv_expand_ordered() {
+    let z = Zenv::new("tests/.env.expanded", false).expand().unwrap();
+
+    let keys: Vec<&str> = z.iter().take(5).map(|(k, _)| k.as_str()).collect();
+
+    assert_eq!(
+        keys,
+        vec![
+            "BASIC",
+            "EXPANDED",
+            "DOUBLE_EXPANDED",
+            "EXPANDED_NEW",
+            "DOUBLE_EXPANDED_NEW"
+        ]
+    );
+    assert_eq!(z[4].1, "basic_basic-basic-expanded");
+}
+
+#[test]
+fn zenv_heredoc() {
+    use zenv::ParseOptions;
+
+    let z = Zenv::new("tests/.env.heredoc", false)
+        .parse_options(ParseOptions {
+            heredoc: true,
+            ..Default::default()
+        })
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("BEFORE").unwrap(), "before");
+    assert_eq!(
+        z.get("CERT").unwrap(),
+        "-----BEGIN CERTIFICATE-----\n  indented # not a comment\n\n-----END CERTIFICATE-----"
+    );
+    assert_eq!(z.get("AFTER").unwrap(), "after");
+    assert_eq!(z.get("EOF"), None);
+}
+
+#[test]
+fn zenv_multiple_files() {
+    let z = Zenv::new("tests/.env.base", true)
+        .file("tests/.env.local")
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("HOST").unwrap(), "localhost");
+    assert_eq!(z.get("PORT").unwrap(), "80");
+    assert_eq!(z.get("DIRS").unwrap(), "/usr/bin:/extra/bin");
+    assert_eq!(z.get("FLAGS").unwrap(), "--verbose");
+    assert_eq!(z.get("URL").unwrap(), "http://localhost:80");
+}
+
+#[test]
+fn zenv_forward_reference() {
+    // The referencing file is listed before the one which defines the variables
+    let z = Zenv::new("tests/.env.ref", true)
+        .file("tests/.env.domain")
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("HOST").unwrap(), "api.example.com");
+    assert_eq!(z.get("URL").unwrap(), "http://api.example.com:80");
+
+    // A chain of references, where each file is referenced by the one before it
+    let z = Zenv::from_text("a.env", "A=\"${B}/a\"", true)
+        .text("b.env", "B=\"${C}/b\"")
+        .text("c.env", "C=\"${D}/c\"")
+        .text("d.env", "D=d")
+        .env_fallback(false)
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("A").unwrap(), "d/c/b/a");
+    assert_eq!(z.get("B").unwrap(), "d/c/b");
+
+    // Same as the other way around
+    let z = Zenv::new("tests/.env.domain", true)
+        .file("tests/.env.ref")
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("URL").unwrap(), "http://api.example.com:80");
+}
+
+#[test]
+fn zenv_parse_with_source() {
+    use std::path::PathBuf;
+
+    let z = Zenv::new("tests/.env.base", true)
+        .file("tests/.env.local")
+        .var("EXTRA", "extra")
+        .parse_with_source()
+        .unwrap();
+
+    let base = PathBuf::from("tests/.env.base");
+    let local = PathBuf::from("tests/.env.local");
+
+    assert_eq!(z["PORT"], ("80".to_string(), base));
+    assert_eq!(z["HOST"], ("localhost".to_string(), local.clone()));
+    assert_eq!(
+        z["DIRS"],
+        ("/usr/bin:/extra/bin".to_string(), local.clone())
+    );
+    assert_eq!(z["URL"], ("http://localhost:80".to_string(), local));
+    assert_eq!(z["EXTRA"], ("extra".to_string(), PathBuf::new()));
+}
+
+#[test]
+fn zenv_parse_with_warnings() {
+    use zenv::WarningKind;
+
+    let mut warnings = Vec::new();
+
+    let z = Zenv::new("tests/.env.base", true)
+        .file("tests/.env.local")
+        .text("inline", "PORT=81\nnot a pair\nPORT=82\nDIRS+=:/bin")
+        .parse_with_warnings(&mut warnings)
+        .unwrap();
+
+    assert_eq!(z["PORT"], "82");
+    assert_eq!(z["URL"], "http://localhost:82");
+
+    let found: Vec<_> = warnings.iter().map(|w| (w.kind, w.line)).collect();
+
+    assert_eq!(
+        found,
+        [
+            (WarningKind::Override, 1),
+            (WarningKind::Dropped, 2),
+            (WarningKind::Override, 1),
+            (WarningKind::Duplicate, 3),
+        ]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "tests/.env.local:1: `HOST` overrides the value of tests/.env.base:1"
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "inline:2: Expected `KEY=value`, the line is dropped - `not a pair`"
+    );
+    assert_eq!(
+        warnings[3].to_string(),
+        "inline:3: Duplicate key, already defined on line 1"
+    );
+}
+
+#[test]
+fn zenv_raw_and_expanded() {
+    use zenv::{ExpandOptions, Lines};
+
+    let lines = Lines::from("BASIC=basic\nEXPANDED=\"${BASIC}_expanded\"");
+
+    let raw = lines.to_hash_map();
+    let expanded = lines.expand_with(&ExpandOptions::default());
+
+    assert_eq!(raw.get("EXPANDED").unwrap(), "${BASIC}_expanded");
+    assert_eq!(expanded.get("EXPANDED").unwrap(), "basic_expanded");
+
+    let (raw, expanded) = Zenv::new("tests/.env.expanded", true)
+        .raw_and_expanded()
+        .unwrap();
+
+    assert_eq!(raw.len(), expanded.len());
+    assert_eq!(raw[3], ("EXPANDED_NEW".into(), "${BASIC}_expanded".into()));
+    assert_eq!(
+        expanded[3],
+        ("EXPANDED_NEW".into(), "basic_expanded".into())
+    );
+}
+
+#[test]
+fn zenv_precedence() {
+    std::env::set_var("HOST", "from_env");
+    std::env::set_var("PORT", "8080");
+
+    let url = |z: Zenv| z.parse().unwrap().get("URL").cloned().unwrap();
+
+    // The process is only used if the files don't define the variable
+    let z = Zenv::new("tests/.env.local", true);
+    assert_eq!(url(z), "http://localhost:8080");
+
+    // Later file takes precedence over the earlier file
+    let z = Zenv::new("tests/.env.base", true).file("tests/.env.local");
+    assert_eq!(url(z), "http://localhost:80");
+
+    // And the variables set directly take precedence over every file
+    let z = Zenv::new("tests/.env.base", true)
+        .file("tests/.env.local")
+        .var("HOST", "from_var");
+    assert_eq!(url(z), "http://from_var:80");
+}
+
+#[test]
+fn zenv_encoding() {
+    use zenv::Encoding;
+
+    let err = Zenv::new("tests/.env.latin1", false).parse().unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("tests/.env.latin1: The file is not valid UTF-8"));
+
+    let z = Zenv::new("tests/.env.latin1", false)
+        .encoding(Encoding::Latin1)
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("NAME").unwrap(), "José");
+    assert_eq!(z.get("CITY").unwrap(), "München");
+
+    let z = Zenv::new("tests/.env.latin1", false)
+        .encoding(Encoding::Lossy)
+        .parse()
+        .unwrap();
+
+    assert_eq!(z.get("NAME").unwrap(), "Jos\u{FFFD}");
+}
+
+#[test]
+fn zenv_nul_byte() {
+    let err = Zenv::new("tests/.env.nul", false).parse().unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "tests/.env.nul: Line 2: NUL byte is not allowed - `BAD=bad\0value`"
+    );
+}
+
+#[test]
+fn zenv_empty() {
+    let z = Zenv::new("tests/.env.empty", true).parse().unwrap();
+
+    assert!(z.is_empty());
+}